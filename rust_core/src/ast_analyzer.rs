@@ -10,17 +10,22 @@
 //! - Comprehensive error handling
 
 use pyo3::prelude::*;
-use pyo3::types::PyDict;
-use pyo3::exceptions::{PyIOError, PyValueError};
-use std::collections::HashMap;
+use pyo3::types::{PyDict, PyList, PyTuple};
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::process::Command;
 use rayon::prelude::*;
 use walkdir::WalkDir;
-use anyhow::{Context, Result as AnyhowResult};
-use log::{debug, warn, info};
+use rustpython_parser::{ast, ast::Ranged, Parse};
 
-/// Complexity metrics for a code element
+/// Complexity metrics for a code element.
+///
+/// Line counters come from a tokenizer pass (see `classify_source_lines`)
+/// that tracks string/comment state across physical lines: every line of a
+/// triple-quoted string (a blank one included) counts as source, and a `#`
+/// inside a string literal never starts a comment. Complexity counters come
+/// from the parsed AST, so a keyword such as `for` inside a docstring is
+/// never counted as a branch.
 #[derive(Debug, Clone)]
 #[pyclass]
 pub struct ComplexityMetrics {
@@ -31,7 +36,13 @@ pub struct ComplexityMetrics {
     #[pyo3(get)]
     pub max_nesting_depth: usize,
     #[pyo3(get)]
-    pub lines_of_code: usize,
+    pub source_lines_of_code: usize,
+    #[pyo3(get)]
+    pub comment_lines: usize,
+    #[pyo3(get)]
+    pub blank_lines: usize,
+    #[pyo3(get)]
+    pub logical_lines: usize,
 }
 
 #[pymethods]
@@ -43,11 +54,14 @@ impl ComplexityMetrics {
 
     fn __repr__(&self) -> String {
         format!(
-            "ComplexityMetrics(cyclomatic={}, cognitive={}, nesting={}, loc={})",
+            "ComplexityMetrics(cyclomatic={}, cognitive={}, nesting={}, sloc={}, comments={}, blank={}, logical={})",
             self.cyclomatic_complexity,
             self.cognitive_complexity,
             self.max_nesting_depth,
-            self.lines_of_code
+            self.source_lines_of_code,
+            self.comment_lines,
+            self.blank_lines,
+            self.logical_lines
         )
     }
 }
@@ -58,15 +72,494 @@ impl Default for ComplexityMetrics {
             cyclomatic_complexity: 1,
             cognitive_complexity: 0,
             max_nesting_depth: 0,
-            lines_of_code: 0,
+            source_lines_of_code: 0,
+            comment_lines: 0,
+            blank_lines: 0,
+            logical_lines: 0,
+        }
+    }
+}
+
+/// Complexity metrics for a single function, method, or class body,
+/// as found by walking the real Python AST.
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct FunctionMetrics {
+    #[pyo3(get)]
+    pub name: String,
+    #[pyo3(get)]
+    pub start_line: usize,
+    #[pyo3(get)]
+    pub end_line: usize,
+    #[pyo3(get)]
+    pub metrics: ComplexityMetrics,
+}
+
+#[pymethods]
+impl FunctionMetrics {
+    fn __repr__(&self) -> String {
+        format!(
+            "FunctionMetrics(name={:?}, lines={}-{}, {})",
+            self.name,
+            self.start_line,
+            self.end_line,
+            self.metrics.__repr__()
+        )
+    }
+}
+
+/// Running totals accumulated while walking a block of statements.
+///
+/// Only tracks cyclomatic complexity and nesting depth: cognitive
+/// complexity is computed separately by `CognitiveWalker`/`cognitive_rules`
+/// (see `cognitive_complexity_for`), not duplicated here.
+#[derive(Default)]
+struct ComplexityAccumulator {
+    cyclomatic: usize,
+    max_nesting: usize,
+}
+
+/// Converts byte offsets into 1-indexed line numbers for a source string.
+struct LineIndex {
+    starts: Vec<usize>,
+}
+
+impl LineIndex {
+    fn new(source: &str) -> Self {
+        let mut starts = vec![0usize];
+        for (idx, ch) in source.char_indices() {
+            if ch == '\n' {
+                starts.push(idx + 1);
+            }
         }
+        Self { starts }
+    }
+
+    fn line_for_offset(&self, offset: usize) -> usize {
+        self.starts.partition_point(|&start| start <= offset)
+    }
+
+    /// Returns the (1-indexed line, byte-offset-within-line column) for a
+    /// byte offset into the source.
+    fn line_and_col(&self, offset: usize) -> (usize, usize) {
+        let line = self.line_for_offset(offset);
+        let line_start = self.starts[line - 1];
+        (line, offset - line_start)
+    }
+}
+
+/// A single, tunable cognitive-complexity scoring rule. When a node of
+/// `node_kind` is visited, `increment` is added to its raw score; if
+/// `nesting` is set, the node's children are walked one nesting level
+/// deeper; and `nesting_increment` is added to the score for every level
+/// of nesting already accumulated at that point (mirroring SonarSource's
+/// "structural increment" vs. "nesting increment" distinction).
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct CognitiveRule {
+    #[pyo3(get, set)]
+    pub name: String,
+    #[pyo3(get, set)]
+    pub node_kind: String,
+    #[pyo3(get, set)]
+    pub increment: f64,
+    #[pyo3(get, set)]
+    pub nesting: bool,
+    #[pyo3(get, set)]
+    pub nesting_increment: f64,
+}
+
+#[pymethods]
+impl CognitiveRule {
+    #[new]
+    #[pyo3(signature = (name, node_kind, increment, nesting, nesting_increment = 1.0))]
+    fn new(
+        name: String,
+        node_kind: String,
+        increment: f64,
+        nesting: bool,
+        nesting_increment: f64,
+    ) -> Self {
+        Self {
+            name,
+            node_kind,
+            increment,
+            nesting,
+            nesting_increment,
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "CognitiveRule(name={:?}, node_kind={:?}, increment={}, nesting={}, nesting_increment={})",
+            self.name, self.node_kind, self.increment, self.nesting, self.nesting_increment
+        )
+    }
+}
+
+impl CognitiveRule {
+    /// A default ruleset approximating SonarSource's cognitive complexity:
+    /// `if`/`for`/`while`/`except` each add a structural point and nest;
+    /// ternaries and comprehension clauses add a point each; a run of
+    /// boolean operators in one expression adds one point per extra operand
+    /// without increasing nesting.
+    fn default_ruleset() -> Vec<CognitiveRule> {
+        vec![
+            CognitiveRule::new("if".to_string(), "If".to_string(), 1.0, true, 1.0),
+            CognitiveRule::new("for".to_string(), "For".to_string(), 1.0, true, 1.0),
+            CognitiveRule::new("while".to_string(), "While".to_string(), 1.0, true, 1.0),
+            CognitiveRule::new(
+                "except".to_string(),
+                "ExceptHandler".to_string(),
+                1.0,
+                true,
+                1.0,
+            ),
+            CognitiveRule::new("ternary".to_string(), "IfExp".to_string(), 1.0, true, 1.0),
+            CognitiveRule::new(
+                "boolean-operator-sequence".to_string(),
+                "BoolOp".to_string(),
+                1.0,
+                false,
+                0.0,
+            ),
+            CognitiveRule::new(
+                "comprehension-for".to_string(),
+                "Comprehension".to_string(),
+                1.0,
+                false,
+                1.0,
+            ),
+            CognitiveRule::new(
+                "comprehension-if".to_string(),
+                "ComprehensionIf".to_string(),
+                1.0,
+                false,
+                1.0,
+            ),
+            CognitiveRule::new("match-case".to_string(), "Match".to_string(), 1.0, true, 1.0),
+        ]
     }
 }
 
+/// One line of the per-node cognitive-complexity explanation produced by
+/// `analyze_cognitive_complexity`: which rule(s) fired, where, at what
+/// nesting level, and for how many points.
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct CognitiveComplexityReport {
+    #[pyo3(get)]
+    pub score: f64,
+    #[pyo3(get)]
+    pub nesting_level: usize,
+    #[pyo3(get)]
+    pub reason: String,
+    #[pyo3(get)]
+    pub node_kind: String,
+    #[pyo3(get)]
+    pub start_line: usize,
+    #[pyo3(get)]
+    pub end_line: usize,
+    #[pyo3(get)]
+    pub col_range: (usize, usize),
+}
+
+#[pymethods]
+impl CognitiveComplexityReport {
+    fn __repr__(&self) -> String {
+        format!(
+            "CognitiveComplexityReport(score={}, nesting_level={}, reason={:?}, node_kind={:?}, lines={}-{})",
+            self.score, self.nesting_level, self.reason, self.node_kind, self.start_line, self.end_line
+        )
+    }
+}
+
+/// Walks the AST accumulating `CognitiveComplexityReport`s according to a
+/// configurable `Vec<CognitiveRule>`, rather than the fixed keyword-driven
+/// increments `analyze_source` used to apply.
+struct CognitiveWalker<'a> {
+    rules: &'a [CognitiveRule],
+    line_index: &'a LineIndex,
+    reports: Vec<CognitiveComplexityReport>,
+    /// Mirrors `walk_stmts`'s `descend_into_defs`: `true` for a
+    /// file-wide walk (nested defs contribute to the total), `false` for a
+    /// single function/method/class's own score (nested defs are scored as
+    /// their own separate entries).
+    descend_into_defs: bool,
+}
+
+impl<'a> CognitiveWalker<'a> {
+    fn rules_for(&self, kind: &str) -> Vec<&CognitiveRule> {
+        self.rules.iter().filter(|r| r.node_kind == kind).collect()
+    }
+
+    /// Applies every rule matching `kind` to a node spanning `[start, end)`
+    /// at the given nesting `level`, recording a report when the combined
+    /// score is non-zero. `multiplier` lets one AST node stand in for
+    /// several logical branches (e.g. a `BoolOp` with N operands). Returns
+    /// whether any matching rule requests deeper nesting for this node's
+    /// children.
+    fn apply_rules(&mut self, kind: &str, multiplier: f64, level: usize, start: usize, end: usize) -> bool {
+        let matched = self.rules_for(kind);
+        if matched.is_empty() {
+            return false;
+        }
+
+        let increment: f64 = matched.iter().map(|r| r.increment).sum::<f64>() * multiplier;
+        let nesting_increment: f64 = matched.iter().map(|r| r.nesting_increment).sum::<f64>() * multiplier;
+        let nests = matched.iter().any(|r| r.nesting);
+        let score = increment + nesting_increment * (level as f64);
+
+        if score != 0.0 {
+            let reason = matched
+                .iter()
+                .map(|r| r.name.as_str())
+                .collect::<Vec<_>>()
+                .join("+");
+            let (start_line, start_col) = self.line_index.line_and_col(start);
+            let (end_line, end_col) = self.line_index.line_and_col(end);
+            self.reports.push(CognitiveComplexityReport {
+                score,
+                nesting_level: level,
+                reason,
+                node_kind: kind.to_string(),
+                start_line,
+                end_line,
+                col_range: (start_col, end_col),
+            });
+        }
+
+        nests
+    }
+
+    fn visit_stmts(&mut self, stmts: &[ast::Stmt], level: usize) {
+        for stmt in stmts {
+            self.visit_stmt(stmt, level);
+        }
+    }
+
+    fn visit_stmt(&mut self, stmt: &ast::Stmt, level: usize) {
+        match stmt {
+            ast::Stmt::If(node) => {
+                let nests = self.apply_rules(
+                    "If",
+                    1.0,
+                    level,
+                    node.range().start().to_usize(),
+                    node.range().end().to_usize(),
+                );
+                self.visit_expr(&node.test, level);
+                self.visit_stmts(&node.body, if nests { level + 1 } else { level });
+                self.visit_stmts(&node.orelse, level);
+            }
+            ast::Stmt::For(node) => {
+                let nests = self.apply_rules(
+                    "For",
+                    1.0,
+                    level,
+                    node.range().start().to_usize(),
+                    node.range().end().to_usize(),
+                );
+                self.visit_expr(&node.iter, level);
+                self.visit_stmts(&node.body, if nests { level + 1 } else { level });
+                self.visit_stmts(&node.orelse, level);
+            }
+            ast::Stmt::AsyncFor(node) => {
+                let nests = self.apply_rules(
+                    "For",
+                    1.0,
+                    level,
+                    node.range().start().to_usize(),
+                    node.range().end().to_usize(),
+                );
+                self.visit_expr(&node.iter, level);
+                self.visit_stmts(&node.body, if nests { level + 1 } else { level });
+                self.visit_stmts(&node.orelse, level);
+            }
+            ast::Stmt::While(node) => {
+                let nests = self.apply_rules(
+                    "While",
+                    1.0,
+                    level,
+                    node.range().start().to_usize(),
+                    node.range().end().to_usize(),
+                );
+                self.visit_expr(&node.test, level);
+                self.visit_stmts(&node.body, if nests { level + 1 } else { level });
+                self.visit_stmts(&node.orelse, level);
+            }
+            ast::Stmt::Try(node) => {
+                self.visit_stmts(&node.body, level);
+                for handler in &node.handlers {
+                    let ast::ExceptHandler::ExceptHandler(handler) = handler;
+                    let nests = self.apply_rules(
+                        "ExceptHandler",
+                        1.0,
+                        level,
+                        handler.range().start().to_usize(),
+                        handler.range().end().to_usize(),
+                    );
+                    self.visit_stmts(&handler.body, if nests { level + 1 } else { level });
+                }
+                self.visit_stmts(&node.orelse, level);
+                self.visit_stmts(&node.finalbody, level);
+            }
+            ast::Stmt::With(node) => self.visit_stmts(&node.body, level),
+            ast::Stmt::AsyncWith(node) => self.visit_stmts(&node.body, level),
+            ast::Stmt::FunctionDef(node) => {
+                if self.descend_into_defs {
+                    self.visit_stmts(&node.body, level);
+                }
+            }
+            ast::Stmt::AsyncFunctionDef(node) => {
+                if self.descend_into_defs {
+                    self.visit_stmts(&node.body, level);
+                }
+            }
+            ast::Stmt::ClassDef(node) => {
+                if self.descend_into_defs {
+                    self.visit_stmts(&node.body, level);
+                }
+            }
+            ast::Stmt::Assign(node) => self.visit_expr(&node.value, level),
+            ast::Stmt::AugAssign(node) => self.visit_expr(&node.value, level),
+            ast::Stmt::AnnAssign(node) => {
+                if let Some(value) = &node.value {
+                    self.visit_expr(value, level);
+                }
+            }
+            ast::Stmt::Return(node) => {
+                if let Some(value) = &node.value {
+                    self.visit_expr(value, level);
+                }
+            }
+            ast::Stmt::Expr(node) => self.visit_expr(&node.value, level),
+            ast::Stmt::Assert(node) => self.visit_expr(&node.test, level),
+            ast::Stmt::Match(node) => {
+                self.visit_expr(&node.subject, level);
+                for case in &node.cases {
+                    let (start, end) = match_case_range(case);
+                    let nests = self.apply_rules("Match", 1.0, level, start, end);
+                    if let Some(guard) = &case.guard {
+                        self.visit_expr(guard, level);
+                    }
+                    self.visit_stmts(&case.body, if nests { level + 1 } else { level });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &ast::Expr, level: usize) {
+        match expr {
+            ast::Expr::BoolOp(node) => {
+                let extra = node.values.len().saturating_sub(1) as f64;
+                if extra > 0.0 {
+                    self.apply_rules(
+                        "BoolOp",
+                        extra,
+                        level,
+                        node.range().start().to_usize(),
+                        node.range().end().to_usize(),
+                    );
+                }
+                for value in &node.values {
+                    self.visit_expr(value, level);
+                }
+            }
+            ast::Expr::IfExp(node) => {
+                let nests = self.apply_rules(
+                    "IfExp",
+                    1.0,
+                    level,
+                    node.range().start().to_usize(),
+                    node.range().end().to_usize(),
+                );
+                self.visit_expr(&node.test, level);
+                self.visit_expr(&node.body, if nests { level + 1 } else { level });
+                self.visit_expr(&node.orelse, if nests { level + 1 } else { level });
+            }
+            ast::Expr::ListComp(node) => self.visit_comprehensions(&node.generators, level),
+            ast::Expr::SetComp(node) => self.visit_comprehensions(&node.generators, level),
+            ast::Expr::DictComp(node) => self.visit_comprehensions(&node.generators, level),
+            ast::Expr::GeneratorExp(node) => self.visit_comprehensions(&node.generators, level),
+            ast::Expr::Call(node) => {
+                self.visit_expr(&node.func, level);
+                for arg in &node.args {
+                    self.visit_expr(arg, level);
+                }
+            }
+            ast::Expr::BinOp(node) => {
+                self.visit_expr(&node.left, level);
+                self.visit_expr(&node.right, level);
+            }
+            ast::Expr::UnaryOp(node) => self.visit_expr(&node.operand, level),
+            ast::Expr::Compare(node) => {
+                self.visit_expr(&node.left, level);
+                for comparator in &node.comparators {
+                    self.visit_expr(comparator, level);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_comprehensions(&mut self, generators: &[ast::Comprehension], level: usize) {
+        for generator in generators {
+            let (start, end) = comprehension_range(generator);
+            self.apply_rules("Comprehension", 1.0, level, start, end);
+            for if_clause in &generator.ifs {
+                self.apply_rules(
+                    "ComprehensionIf",
+                    1.0,
+                    level,
+                    if_clause.range().start().to_usize(),
+                    if_clause.range().end().to_usize(),
+                );
+                self.visit_expr(if_clause, level);
+            }
+        }
+    }
+}
+
+/// `ast::Comprehension` only implements `Ranged` under rustpython-ast's
+/// non-default `all-nodes-with-ranges` feature, which this crate doesn't
+/// enable, so its span is reconstructed from its `target`/`iter`/`ifs`
+/// (plain `Expr`s, unconditionally `Ranged`) instead of calling
+/// `.range()` on the comprehension itself. Mirrors `match_case_range`
+/// below, which works around the same gap for `MatchCase`.
+fn comprehension_range(generator: &ast::Comprehension) -> (usize, usize) {
+    let start = generator.target.range().start().to_usize();
+    let end = generator
+        .ifs
+        .last()
+        .map(|if_clause| if_clause.range().end())
+        .unwrap_or_else(|| generator.iter.range().end())
+        .to_usize();
+    (start, end)
+}
+
+/// `ast::MatchCase` carries no position attributes of its own (mirroring
+/// CPython's `match_case` grammar node, which isn't itself a located node),
+/// so its span is reconstructed from its pattern's start and its last body
+/// statement's end (falling back to the pattern's own end for an empty
+/// body, which the grammar otherwise never produces).
+fn match_case_range(case: &ast::MatchCase) -> (usize, usize) {
+    let start = case.pattern.range().start().to_usize();
+    let end = case
+        .body
+        .last()
+        .map(|stmt| stmt.range().end())
+        .unwrap_or_else(|| case.pattern.range().end())
+        .to_usize();
+    (start, end)
+}
+
 /// High-performance AST analyzer
 #[pyclass]
 pub struct RustAstAnalyzer {
-    cache: HashMap<String, ComplexityMetrics>,
+    cache: HashMap<String, (ComplexityMetrics, Vec<FunctionMetrics>)>,
+    cognitive_rules: Vec<CognitiveRule>,
 }
 
 #[pymethods]
@@ -75,15 +568,63 @@ impl RustAstAnalyzer {
     fn new() -> Self {
         Self {
             cache: HashMap::new(),
+            cognitive_rules: CognitiveRule::default_ruleset(),
         }
     }
 
-    /// Analyze a single Python file
+    /// Builds an analyzer with a custom cognitive-complexity ruleset instead
+    /// of the SonarSource-like default, so teams can retune weights without
+    /// touching Rust code.
+    #[staticmethod]
+    fn with_rules(rules: Vec<CognitiveRule>) -> Self {
+        Self {
+            cache: HashMap::new(),
+            cognitive_rules: rules,
+        }
+    }
+
+    /// Returns the default cognitive-complexity ruleset, as a starting point
+    /// for teams that want to tune a handful of weights with `with_rules`.
+    #[staticmethod]
+    fn default_cognitive_rules() -> Vec<CognitiveRule> {
+        CognitiveRule::default_ruleset()
+    }
+
+    /// Computes a per-node cognitive-complexity breakdown for `source` using
+    /// this analyzer's ruleset. Returns a `(total_score, list[
+    /// CognitiveComplexityReport])` tuple, where `reason` on each report is
+    /// the `+`-joined names of every rule that fired for that node.
+    fn analyze_cognitive_complexity(&self, py: Python, source: &str) -> PyResult<PyObject> {
+        let suite = ast::Suite::parse(source, "<module>")
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
+        let line_index = LineIndex::new(source);
+        let mut walker = CognitiveWalker {
+            rules: &self.cognitive_rules,
+            line_index: &line_index,
+            reports: Vec::new(),
+            descend_into_defs: true,
+        };
+        walker.visit_stmts(&suite, 0);
+
+        let total: f64 = walker.reports.iter().map(|r| r.score).sum();
+        let report_list = PyList::empty(py);
+        for report in walker.reports {
+            report_list.append(Py::new(py, report)?)?;
+        }
+        let tuple = PyTuple::new(py, &[total.into_py(py), report_list.into_py(py)]);
+        Ok(tuple.into())
+    }
+
+    /// Analyze a single Python file.
+    ///
+    /// Returns a `(ComplexityMetrics, list[FunctionMetrics])` tuple: the
+    /// first element is the file-wide aggregate, the second is a
+    /// per-function/per-method breakdown.
     #[pyo3(signature = (file_path))]
-    fn analyze_file(&mut self, file_path: String) -> PyResult<ComplexityMetrics> {
+    fn analyze_file(&mut self, py: Python, file_path: String) -> PyResult<PyObject> {
         // Check cache
-        if let Some(metrics) = self.cache.get(&file_path) {
-            return Ok(metrics.clone());
+        if let Some((metrics, functions)) = self.cache.get(&file_path) {
+            return Self::pack_result(py, metrics.clone(), functions.clone());
         }
 
         // Read file
@@ -91,21 +632,30 @@ impl RustAstAnalyzer {
             .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(e.to_string()))?;
 
         // Parse and analyze
-        let metrics = self.analyze_source(&content)?;
+        let (metrics, functions) = self.compute_metrics(&content)?;
 
         // Cache result
-        self.cache.insert(file_path, metrics.clone());
+        self.cache
+            .insert(file_path, (metrics.clone(), functions.clone()));
 
-        Ok(metrics)
+        Self::pack_result(py, metrics, functions)
     }
 
-    /// Analyze entire directory in parallel
-    #[pyo3(signature = (directory_path, extensions = None))]
+    /// Analyze entire directory in parallel.
+    ///
+    /// By default returns a dict of `file -> (ComplexityMetrics,
+    /// list[FunctionMetrics])`. When `git_hotspots` is set, each analyzed
+    /// file is additionally blamed via `git blame`/`git log` and the
+    /// result is instead a list of `HotspotScore`, sorted by descending
+    /// `risk`, so complex code that also churns often and has many
+    /// authors surfaces first. Files not tracked by git are skipped.
+    #[pyo3(signature = (directory_path, extensions = None, git_hotspots = false))]
     fn analyze_directory(
         &mut self,
         py: Python,
         directory_path: String,
         extensions: Option<Vec<String>>,
+        git_hotspots: bool,
     ) -> PyResult<PyObject> {
         let default_exts = vec!["py".to_string()];
         let exts = extensions.as_ref().unwrap_or(&default_exts);
@@ -127,133 +677,707 @@ impl RustAstAnalyzer {
 
         println!("🔍 Analyzing {} files...", files.len());
 
+        // Cloned so the rayon closure below can borrow it alongside `&self`
+        // without fighting the borrow checker over `&mut self.cache` elsewhere.
+        let cognitive_rules = self.cognitive_rules.clone();
+
         // Parallel processing with rayon
-        let results: HashMap<String, ComplexityMetrics> = files
+        let results: HashMap<String, (ComplexityMetrics, Vec<FunctionMetrics>)> = files
             .par_iter()
-            .filter_map(|file_path| {
-                match std::fs::read_to_string(file_path) {
-                    Ok(content) => {
-                        match self.analyze_source(&content) {
-                            Ok(metrics) => Some((file_path.clone(), metrics)),
-                            Err(_) => None,
-                        }
-                    }
+            .filter_map(
+                |file_path| match std::fs::read_to_string(file_path) {
+                    Ok(content) => match Self::compute_metrics_static(&content, &cognitive_rules) {
+                        Ok(result) => Some((file_path.clone(), result)),
+                        Err(_) => None,
+                    },
                     Err(_) => None,
-                }
+                },
+            )
+            .collect();
+
+        if !git_hotspots {
+            // Convert to Python dict of file -> (ComplexityMetrics, list[FunctionMetrics])
+            let dict = PyDict::new(py);
+            for (file, (metrics, functions)) in results.iter() {
+                let packed = Self::pack_result(py, metrics.clone(), functions.clone())?;
+                dict.set_item(file, packed)?;
+            }
+            return Ok(dict.into());
+        }
+
+        let mut hotspots: Vec<HotspotScore> = results
+            .par_iter()
+            .filter_map(|(file, (metrics, _functions))| {
+                compute_hotspot_score(&directory_path, file, metrics.cyclomatic_complexity)
             })
             .collect();
+        hotspots.sort_by(|a, b| b.risk.partial_cmp(&a.risk).unwrap_or(std::cmp::Ordering::Equal));
 
-        // Convert to Python dict
-        let dict = PyDict::new(py);
-        for (file, metrics) in results.iter() {
-            // Convert ComplexityMetrics to Py<ComplexityMetrics>
-            let py_metrics = Py::new(py, metrics.clone())?;
-            dict.set_item(file, py_metrics)?;
+        let list = PyList::empty(py);
+        for hotspot in hotspots {
+            list.append(Py::new(py, hotspot)?)?;
         }
+        Ok(list.into())
+    }
 
-        Ok(dict.into())
+    /// Analyze Python source code.
+    ///
+    /// Parses the source into a real Python AST and walks it, rather than
+    /// matching line prefixes against a keyword list. Returns a
+    /// `(ComplexityMetrics, list[FunctionMetrics])` tuple: the first element
+    /// is the file-wide aggregate, the second is a per-function/per-method
+    /// breakdown.
+    fn analyze_source(&self, py: Python, source: &str) -> PyResult<PyObject> {
+        let (metrics, functions) = self.compute_metrics(source)?;
+        Self::pack_result(py, metrics, functions)
     }
 
-    /// Analyze Python source code
-    fn analyze_source(&self, source: &str) -> PyResult<ComplexityMetrics> {
-        // Parse Python AST using syn (simplified for now)
-        // In production, use tree-sitter-python for full Python parsing
-        
+    /// Clear analysis cache
+    fn clear_cache(&mut self) {
+        self.cache.clear();
+    }
+
+    /// Get cache size
+    fn cache_size(&self) -> usize {
+        self.cache.len()
+    }
+}
+
+impl RustAstAnalyzer {
+    /// Builds the `(ComplexityMetrics, list[FunctionMetrics])` Python return
+    /// value, following the repo convention (see `correlator.rs`) of
+    /// manually assembling containers of pyclass instances rather than
+    /// relying on automatic conversion.
+    fn pack_result(
+        py: Python,
+        metrics: ComplexityMetrics,
+        functions: Vec<FunctionMetrics>,
+    ) -> PyResult<PyObject> {
+        let func_list = PyList::empty(py);
+        for func in functions {
+            func_list.append(Py::new(py, func)?)?;
+        }
+        let metrics_obj = Py::new(py, metrics)?;
+        let tuple = PyTuple::new(py, &[metrics_obj.into_py(py), func_list.into_py(py)]);
+        Ok(tuple.into())
+    }
+
+    fn compute_metrics(&self, source: &str) -> PyResult<(ComplexityMetrics, Vec<FunctionMetrics>)> {
+        Self::compute_metrics_static(source, &self.cognitive_rules)
+    }
+
+    /// Parses `source` and computes both the file-wide aggregate metrics
+    /// and a per-function/per-method/per-class breakdown.
+    ///
+    /// `cognitive_rules` drives cognitive complexity for both the file-wide
+    /// aggregate and every per-function entry via `CognitiveWalker`, so
+    /// `with_rules` changing `cognitive_rules` changes every cognitive
+    /// complexity number this returns.
+    /// Cyclomatic complexity and nesting depth are unaffected by the
+    /// ruleset and still come from the plain branch-counting walker below.
+    fn compute_metrics_static(
+        source: &str,
+        cognitive_rules: &[CognitiveRule],
+    ) -> PyResult<(ComplexityMetrics, Vec<FunctionMetrics>)> {
+        let suite = ast::Suite::parse(source, "<module>")
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
+
+        let line_index = LineIndex::new(source);
+
+        // File-wide aggregate: walk every statement in the module, descending
+        // into nested functions and classes, so the total reflects every
+        // control-flow construct in the file.
+        let mut file_acc = ComplexityAccumulator::default();
+        walk_stmts(&suite, 0, &mut file_acc, true);
+
+        let line_stats = classify_source_lines(source);
+
         let mut metrics = ComplexityMetrics::default();
-        metrics.lines_of_code = source.lines().count();
-
-        // Calculate complexity (simplified version)
-        // Count control flow statements
-        let control_flow_keywords = ["if", "else", "elif", "for", "while", "try", "except", "with"];
-        let mut complexity = 1;
-        let mut cognitive = 0;
-        let mut max_nesting = 0;
-        let mut current_nesting = 0;
-        let mut indent_stack: Vec<usize> = vec![0]; // Track indentation levels
-
-        for line in source.lines() {
-            let trimmed = line.trim();
-            
-            // Skip empty lines (don't reset nesting)
-            if trimmed.is_empty() {
-                continue;
+        metrics.source_lines_of_code = line_stats.source_lines_of_code;
+        metrics.comment_lines = line_stats.comment_lines;
+        metrics.blank_lines = line_stats.blank_lines;
+        metrics.logical_lines = line_stats.logical_lines;
+        metrics.cyclomatic_complexity = 1 + file_acc.cyclomatic;
+        metrics.max_nesting_depth = file_acc.max_nesting;
+        metrics.cognitive_complexity = cognitive_complexity_for(&suite, &line_index, cognitive_rules, true);
+
+        // Per-function/per-method breakdown: each def gets its own metrics,
+        // computed only from its own body (nested defs are their own entry
+        // and do not contribute to their parent's complexity).
+        let mut functions = Vec::new();
+        collect_functions(&suite, source, &line_index, cognitive_rules, &mut functions);
+
+        Ok((metrics, functions))
+    }
+}
+
+/// Runs `CognitiveWalker` over `stmts` and sums the resulting scores,
+/// rounding to the nearest whole point for `ComplexityMetrics`'s integer
+/// field (rule weights are tunable floats, but the reported complexity is
+/// conventionally a whole number, same as SonarSource's).
+fn cognitive_complexity_for(
+    stmts: &[ast::Stmt],
+    line_index: &LineIndex,
+    rules: &[CognitiveRule],
+    descend_into_defs: bool,
+) -> usize {
+    let mut walker = CognitiveWalker {
+        rules,
+        line_index,
+        reports: Vec::new(),
+        descend_into_defs,
+    };
+    walker.visit_stmts(stmts, 0);
+    let total: f64 = walker.reports.iter().map(|r| r.score).sum();
+    total.round() as usize
+}
+
+/// Recursively finds every function/method/class definition in `stmts`,
+/// computing its own `FunctionMetrics` and recursing into its body to find
+/// nested defs (and into class bodies to find methods). A class gets its
+/// own entry too, scored from its own body only (method bodies are scored
+/// as their own separate entries, same as a nested `def`).
+fn collect_functions(
+    stmts: &[ast::Stmt],
+    source: &str,
+    line_index: &LineIndex,
+    cognitive_rules: &[CognitiveRule],
+    out: &mut Vec<FunctionMetrics>,
+) {
+    for stmt in stmts {
+        match stmt {
+            ast::Stmt::FunctionDef(node) => {
+                out.push(build_function_metrics(
+                    node.name.as_str(),
+                    &node.body,
+                    node.range().start().to_usize(),
+                    node.range().end().to_usize(),
+                    source,
+                    line_index,
+                    cognitive_rules,
+                ));
+                collect_functions(&node.body, source, line_index, cognitive_rules, out);
             }
-            
-            // Calculate current indentation
-            let indent = line.len() - line.trim_start().len();
-            
-            // Update nesting level based on indentation
-            while indent_stack.len() > 1 && indent < *indent_stack.last().unwrap() {
-                indent_stack.pop();
-                if current_nesting > 0 {
-                    current_nesting -= 1;
+            ast::Stmt::AsyncFunctionDef(node) => {
+                out.push(build_function_metrics(
+                    node.name.as_str(),
+                    &node.body,
+                    node.range().start().to_usize(),
+                    node.range().end().to_usize(),
+                    source,
+                    line_index,
+                    cognitive_rules,
+                ));
+                collect_functions(&node.body, source, line_index, cognitive_rules, out);
+            }
+            ast::Stmt::ClassDef(node) => {
+                out.push(build_function_metrics(
+                    node.name.as_str(),
+                    &node.body,
+                    node.range().start().to_usize(),
+                    node.range().end().to_usize(),
+                    source,
+                    line_index,
+                    cognitive_rules,
+                ));
+                collect_functions(&node.body, source, line_index, cognitive_rules, out);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn build_function_metrics(
+    name: &str,
+    body: &[ast::Stmt],
+    range_start: usize,
+    range_end: usize,
+    source: &str,
+    line_index: &LineIndex,
+    cognitive_rules: &[CognitiveRule],
+) -> FunctionMetrics {
+    let mut acc = ComplexityAccumulator::default();
+    // Don't descend into nested defs here: they are collected (and scored)
+    // as their own separate FunctionMetrics entries.
+    walk_stmts(body, 0, &mut acc, false);
+    let cognitive_complexity = cognitive_complexity_for(body, line_index, cognitive_rules, false);
+
+    let start_line = line_index.line_for_offset(range_start);
+    let end_line = line_index.line_for_offset(range_end);
+    let line_stats = classify_source_lines(&lines_in_range(source, start_line, end_line));
+
+    FunctionMetrics {
+        name: name.to_string(),
+        start_line,
+        end_line,
+        metrics: ComplexityMetrics {
+            cyclomatic_complexity: 1 + acc.cyclomatic,
+            cognitive_complexity,
+            max_nesting_depth: acc.max_nesting,
+            source_lines_of_code: line_stats.source_lines_of_code,
+            comment_lines: line_stats.comment_lines,
+            blank_lines: line_stats.blank_lines,
+            logical_lines: line_stats.logical_lines,
+        },
+    }
+}
+
+/// Returns the 1-indexed, inclusive `[start_line, end_line]` slice of
+/// `source`, joined back into text so it can be re-run through
+/// `classify_source_lines` on its own.
+fn lines_in_range(source: &str, start_line: usize, end_line: usize) -> String {
+    source
+        .lines()
+        .skip(start_line.saturating_sub(1))
+        .take(end_line.saturating_sub(start_line) + 1)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// How each physical line of a source snippet is classified by
+/// `classify_source_lines`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LineKind {
+    Blank,
+    Comment,
+    Code,
+}
+
+/// Aggregate line counters produced by a single tokenizer pass over a
+/// source snippet.
+#[derive(Debug, Clone, Copy, Default)]
+struct LineStats {
+    source_lines_of_code: usize,
+    comment_lines: usize,
+    blank_lines: usize,
+    logical_lines: usize,
+}
+
+/// A lightweight Python lexer stage: classifies each physical line as
+/// blank, a full-line comment, or code, tracking triple-quoted string state
+/// across lines so a docstring (or a `#` inside a string literal) is never
+/// mistaken for a comment; docstring lines count as code. Also collapses backslash `\`
+/// continuations and implicit bracket continuations into single logical
+/// lines for `logical_lines`.
+fn classify_source_lines(source: &str) -> LineStats {
+    let mut stats = LineStats::default();
+    let mut in_triple_quote: Option<char> = None;
+    let mut bracket_depth: i64 = 0;
+    let mut continuing_logical_line = false;
+
+    for raw_line in source.lines() {
+        let started_inside_string = in_triple_quote.is_some();
+        let mut line_has_code = false;
+        let mut line_has_comment = false;
+
+        let chars: Vec<char> = raw_line.chars().collect();
+        let mut i = 0;
+        while i < chars.len() {
+            let ch = chars[i];
+
+            if let Some(quote) = in_triple_quote {
+                if ch == quote && chars[i..].starts_with(&[quote, quote, quote]) {
+                    in_triple_quote = None;
+                    i += 3;
+                    continue;
                 }
+                i += 1;
+                continue;
             }
-            
-            // Count control flow with word boundary check
-            for keyword in &control_flow_keywords {
-                // Check if line starts with keyword followed by whitespace, colon, or parenthesis
-                let keyword_pattern = format!("{} ", keyword);
-                let keyword_colon = format!("{}:", keyword);
-                let keyword_paren = format!("{}(", keyword);
-                
-                if trimmed.starts_with(&keyword_pattern) 
-                    || trimmed.starts_with(&keyword_colon)
-                    || trimmed.starts_with(&keyword_paren)
-                    || trimmed == *keyword {  // standalone keyword
-                    
-                    complexity += 1;
-                    cognitive += 1 + current_nesting;
-                    
-                    // Increase nesting for most control flow (but not else/elif/except)
-                    if *keyword != "else" && *keyword != "elif" && *keyword != "except" {
-                        current_nesting += 1;
-                        max_nesting = max_nesting.max(current_nesting);
-                        indent_stack.push(indent);
+
+            match ch {
+                '#' => {
+                    line_has_comment = true;
+                    break;
+                }
+                '\'' | '"' => {
+                    line_has_code = true;
+                    if chars[i..].starts_with(&[ch, ch, ch]) {
+                        in_triple_quote = Some(ch);
+                        i += 3;
+                    } else {
+                        // Single-line string: skip to its closing quote,
+                        // honoring backslash escapes.
+                        i += 1;
+                        while i < chars.len() {
+                            if chars[i] == '\\' {
+                                i += 2;
+                                continue;
+                            }
+                            if chars[i] == ch {
+                                i += 1;
+                                break;
+                            }
+                            i += 1;
+                        }
                     }
-                    
-                    break; // Only count once per line
+                }
+                '(' | '[' | '{' => {
+                    bracket_depth += 1;
+                    line_has_code = true;
+                    i += 1;
+                }
+                ')' | ']' | '}' => {
+                    bracket_depth -= 1;
+                    line_has_code = true;
+                    i += 1;
+                }
+                c if !c.is_whitespace() => {
+                    line_has_code = true;
+                    i += 1;
+                }
+                _ => {
+                    i += 1;
                 }
             }
         }
 
-        metrics.cyclomatic_complexity = complexity;
-        metrics.cognitive_complexity = cognitive;
-        metrics.max_nesting_depth = max_nesting;
+        let ends_with_backslash = in_triple_quote.is_none() && raw_line.trim_end().ends_with('\\');
 
-        Ok(metrics)
+        let kind = if started_inside_string || line_has_code {
+            LineKind::Code
+        } else if line_has_comment {
+            LineKind::Comment
+        } else {
+            LineKind::Blank
+        };
+
+        match kind {
+            LineKind::Code => stats.source_lines_of_code += 1,
+            LineKind::Comment => stats.comment_lines += 1,
+            LineKind::Blank => stats.blank_lines += 1,
+        }
+
+        if kind == LineKind::Code && !continuing_logical_line {
+            stats.logical_lines += 1;
+        }
+
+        continuing_logical_line = in_triple_quote.is_some() || bracket_depth > 0 || ends_with_backslash;
     }
 
-    /// Clear analysis cache
-    fn clear_cache(&mut self) {
-        self.cache.clear();
+    stats
+}
+
+/// A file's static complexity fused with its git history: how often it
+/// changes and how many distinct people have touched it. Ranked by `risk`,
+/// the idea being that complex code which also churns often and has many
+/// authors is the code most worth reviewing first.
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct HotspotScore {
+    #[pyo3(get)]
+    pub file_path: String,
+    #[pyo3(get)]
+    pub complexity: usize,
+    #[pyo3(get)]
+    pub churn: usize,
+    #[pyo3(get)]
+    pub author_count: usize,
+    #[pyo3(get)]
+    pub first_commit_time: i64,
+    #[pyo3(get)]
+    pub last_commit_time: i64,
+    #[pyo3(get)]
+    pub risk: f64,
+}
+
+#[pymethods]
+impl HotspotScore {
+    fn __repr__(&self) -> String {
+        format!(
+            "HotspotScore(file_path={:?}, complexity={}, churn={}, author_count={}, risk={:.3})",
+            self.file_path, self.complexity, self.churn, self.author_count, self.risk
+        )
     }
+}
 
-    /// Get cache size
-    fn cache_size(&self) -> usize {
-        self.cache.len()
+/// Blames and logs `file_path` (a path under `repo_dir`) to build its
+/// `HotspotScore`, fusing `complexity` with churn and authorship. Returns
+/// `None` if the file isn't tracked by git (or git isn't available), so a
+/// directory with some untracked files doesn't fail the whole run.
+fn compute_hotspot_score(repo_dir: &str, file_path: &str, complexity: usize) -> Option<HotspotScore> {
+    let relative = Path::new(file_path)
+        .strip_prefix(repo_dir)
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|_| PathBuf::from(file_path));
+    let relative_str = relative.to_str()?;
+
+    let blame_output = Command::new("git")
+        .current_dir(repo_dir)
+        .args(["blame", "--line-porcelain", "--", relative_str])
+        .output()
+        .ok()?;
+    if !blame_output.status.success() {
+        return None;
+    }
+    let blame_text = String::from_utf8_lossy(&blame_output.stdout);
+    let (authors, first_commit_time, last_commit_time) = parse_blame_porcelain(&blame_text);
+
+    let log_output = Command::new("git")
+        .current_dir(repo_dir)
+        .args(["log", "--format=%H", "--", relative_str])
+        .output()
+        .ok()?;
+    if !log_output.status.success() {
+        return None;
+    }
+    let churn = String::from_utf8_lossy(&log_output.stdout)
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .count();
+
+    let risk = complexity as f64 * (1.0 + churn as f64).ln();
+
+    Some(HotspotScore {
+        file_path: file_path.to_string(),
+        complexity,
+        churn,
+        author_count: authors.len(),
+        first_commit_time,
+        last_commit_time,
+        risk,
+    })
+}
+
+/// Incrementally parses `git blame --line-porcelain` output: a state
+/// machine that resets its per-line accumulator on each 40-hex commit
+/// header and reads the `author-mail`/`author-time` key-value lines that
+/// follow it, committing them once the line's content (prefixed with a
+/// tab) is reached. Relies on `--line-porcelain` repeating full metadata
+/// for every line (unlike plain `--porcelain`, which abbreviates repeats),
+/// so no state needs to carry across chunks.
+fn parse_blame_porcelain(output: &str) -> (HashSet<String>, i64, i64) {
+    let mut authors = HashSet::new();
+    let mut first_time = i64::MAX;
+    let mut last_time = i64::MIN;
+    let mut current_author_mail: Option<String> = None;
+    let mut current_author_time: Option<i64> = None;
+
+    for line in output.lines() {
+        if line.len() > 40
+            && line.as_bytes()[40] == b' '
+            && line.as_bytes()[..40].iter().all(|b| b.is_ascii_hexdigit())
+        {
+            // a new chunk's commit header: start from a fresh accumulator
+            current_author_mail = None;
+            current_author_time = None;
+            continue;
+        }
+
+        if line.starts_with('\t') {
+            // the blamed source line itself; its content isn't needed here
+            if let Some(mail) = current_author_mail.take() {
+                authors.insert(mail);
+            }
+            if let Some(time) = current_author_time.take() {
+                first_time = first_time.min(time);
+                last_time = last_time.max(time);
+            }
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("author-mail ") {
+            current_author_mail = Some(rest.trim().trim_matches(|c| c == '<' || c == '>').to_string());
+        } else if let Some(rest) = line.strip_prefix("author-time ") {
+            current_author_time = rest.trim().parse::<i64>().ok();
+        }
+        // Any other line (`author `, `committer ...`, `summary `,
+        // `filename `, etc.) carries no information we need and is skipped.
+    }
+
+    if first_time == i64::MAX {
+        first_time = 0;
+    }
+    if last_time == i64::MIN {
+        last_time = 0;
+    }
+
+    (authors, first_time, last_time)
+}
+
+/// Walks a block of statements, tallying cyclomatic complexity and max
+/// nesting depth from the real AST structure (cognitive complexity is
+/// computed separately, see `cognitive_complexity_for`). `descend_into_defs`
+/// controls whether nested function/class bodies are folded into the
+/// current total (used for the whole-file aggregate) or treated as opaque
+/// leaves (used for a single function's own metrics).
+fn walk_stmts(stmts: &[ast::Stmt], nesting: usize, acc: &mut ComplexityAccumulator, descend_into_defs: bool) {
+    for stmt in stmts {
+        walk_stmt(stmt, nesting, acc, descend_into_defs);
+    }
+}
+
+fn walk_stmt(stmt: &ast::Stmt, nesting: usize, acc: &mut ComplexityAccumulator, descend_into_defs: bool) {
+    match stmt {
+        ast::Stmt::FunctionDef(node) => {
+            if descend_into_defs {
+                walk_stmts(&node.body, nesting, acc, descend_into_defs);
+            }
+        }
+        ast::Stmt::AsyncFunctionDef(node) => {
+            if descend_into_defs {
+                walk_stmts(&node.body, nesting, acc, descend_into_defs);
+            }
+        }
+        ast::Stmt::ClassDef(node) => {
+            if descend_into_defs {
+                walk_stmts(&node.body, nesting, acc, descend_into_defs);
+            }
+        }
+        ast::Stmt::If(node) => {
+            record_branch(acc, nesting);
+            walk_expr(&node.test, nesting, acc);
+            walk_stmts(&node.body, nesting + 1, acc, descend_into_defs);
+            walk_stmts(&node.orelse, nesting, acc, descend_into_defs);
+        }
+        ast::Stmt::For(node) => {
+            record_branch(acc, nesting);
+            walk_expr(&node.iter, nesting, acc);
+            walk_stmts(&node.body, nesting + 1, acc, descend_into_defs);
+            walk_stmts(&node.orelse, nesting, acc, descend_into_defs);
+        }
+        ast::Stmt::AsyncFor(node) => {
+            record_branch(acc, nesting);
+            walk_expr(&node.iter, nesting, acc);
+            walk_stmts(&node.body, nesting + 1, acc, descend_into_defs);
+            walk_stmts(&node.orelse, nesting, acc, descend_into_defs);
+        }
+        ast::Stmt::While(node) => {
+            record_branch(acc, nesting);
+            walk_expr(&node.test, nesting, acc);
+            walk_stmts(&node.body, nesting + 1, acc, descend_into_defs);
+            walk_stmts(&node.orelse, nesting, acc, descend_into_defs);
+        }
+        ast::Stmt::Try(node) => {
+            walk_stmts(&node.body, nesting + 1, acc, descend_into_defs);
+            for handler in &node.handlers {
+                let ast::ExceptHandler::ExceptHandler(handler) = handler;
+                record_branch(acc, nesting);
+                walk_stmts(&handler.body, nesting + 1, acc, descend_into_defs);
+            }
+            walk_stmts(&node.orelse, nesting, acc, descend_into_defs);
+            walk_stmts(&node.finalbody, nesting, acc, descend_into_defs);
+            acc.max_nesting = acc.max_nesting.max(nesting + 1);
+        }
+        ast::Stmt::With(node) => {
+            acc.max_nesting = acc.max_nesting.max(nesting + 1);
+            walk_stmts(&node.body, nesting + 1, acc, descend_into_defs);
+        }
+        ast::Stmt::AsyncWith(node) => {
+            acc.max_nesting = acc.max_nesting.max(nesting + 1);
+            walk_stmts(&node.body, nesting + 1, acc, descend_into_defs);
+        }
+        ast::Stmt::Assign(node) => walk_expr(&node.value, nesting, acc),
+        ast::Stmt::AugAssign(node) => walk_expr(&node.value, nesting, acc),
+        ast::Stmt::AnnAssign(node) => {
+            if let Some(value) = &node.value {
+                walk_expr(value, nesting, acc);
+            }
+        }
+        ast::Stmt::Return(node) => {
+            if let Some(value) = &node.value {
+                walk_expr(value, nesting, acc);
+            }
+        }
+        ast::Stmt::Expr(node) => walk_expr(&node.value, nesting, acc),
+        ast::Stmt::Assert(node) => walk_expr(&node.test, nesting, acc),
+        ast::Stmt::Match(node) => {
+            walk_expr(&node.subject, nesting, acc);
+            for case in &node.cases {
+                record_branch(acc, nesting);
+                if let Some(guard) = &case.guard {
+                    walk_expr(guard, nesting, acc);
+                }
+                walk_stmts(&case.body, nesting + 1, acc, descend_into_defs);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Records a single branch point (an `if`/`for`/`while`/`except` clause):
+/// one cyclomatic point, and updates the max nesting depth seen so far.
+fn record_branch(acc: &mut ComplexityAccumulator, nesting: usize) {
+    acc.cyclomatic += 1;
+    acc.max_nesting = acc.max_nesting.max(nesting + 1);
+}
+
+fn walk_expr(expr: &ast::Expr, nesting: usize, acc: &mut ComplexityAccumulator) {
+    match expr {
+        ast::Expr::BoolOp(node) => {
+            // Each extra `and`/`or` operand is its own short-circuiting branch.
+            if node.values.len() > 1 {
+                acc.cyclomatic += node.values.len() - 1;
+            }
+            for value in &node.values {
+                walk_expr(value, nesting, acc);
+            }
+        }
+        ast::Expr::IfExp(node) => {
+            record_branch(acc, nesting);
+            walk_expr(&node.test, nesting, acc);
+            walk_expr(&node.body, nesting, acc);
+            walk_expr(&node.orelse, nesting, acc);
+        }
+        ast::Expr::ListComp(node) => walk_comprehensions(&node.generators, nesting, acc),
+        ast::Expr::SetComp(node) => walk_comprehensions(&node.generators, nesting, acc),
+        ast::Expr::DictComp(node) => walk_comprehensions(&node.generators, nesting, acc),
+        ast::Expr::GeneratorExp(node) => walk_comprehensions(&node.generators, nesting, acc),
+        ast::Expr::Call(node) => {
+            walk_expr(&node.func, nesting, acc);
+            for arg in &node.args {
+                walk_expr(arg, nesting, acc);
+            }
+        }
+        ast::Expr::BinOp(node) => {
+            walk_expr(&node.left, nesting, acc);
+            walk_expr(&node.right, nesting, acc);
+        }
+        ast::Expr::UnaryOp(node) => walk_expr(&node.operand, nesting, acc),
+        ast::Expr::Compare(node) => {
+            walk_expr(&node.left, nesting, acc);
+            for comparator in &node.comparators {
+                walk_expr(comparator, nesting, acc);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// A comprehension's `for` clause is itself a branch, and every `if` filter
+/// on it is another.
+fn walk_comprehensions(generators: &[ast::Comprehension], nesting: usize, acc: &mut ComplexityAccumulator) {
+    for generator in generators {
+        record_branch(acc, nesting);
+        for if_clause in &generator.ifs {
+            record_branch(acc, nesting);
+            walk_expr(if_clause, nesting, acc);
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use pyo3::Python;
 
     #[test]
     fn test_simple_function() {
-        let analyzer = RustAstAnalyzer::new();
         let source = r#"
 def simple_function():
     return 42
 "#;
-        let metrics = analyzer.analyze_source(source).unwrap();
-        assert_eq!(metrics.lines_of_code, 4);
+        let (metrics, functions) = RustAstAnalyzer::compute_metrics_static(source, &CognitiveRule::default_ruleset()).unwrap();
+        assert_eq!(metrics.source_lines_of_code, 2);
+        assert_eq!(metrics.blank_lines, 1);
         assert_eq!(metrics.cyclomatic_complexity, 1);
+        assert_eq!(functions.len(), 1);
+        assert_eq!(functions[0].name, "simple_function");
+        assert_eq!(functions[0].metrics.cyclomatic_complexity, 1);
     }
 
     #[test]
     fn test_complex_function() {
-        let analyzer = RustAstAnalyzer::new();
         let source = r#"
 def complex_function(x):
     if x > 10:
@@ -263,8 +1387,284 @@ def complex_function(x):
     else:
         return 0
 "#;
-        let metrics = analyzer.analyze_source(source).unwrap();
+        let (metrics, functions) = RustAstAnalyzer::compute_metrics_static(source, &CognitiveRule::default_ruleset()).unwrap();
         assert!(metrics.cyclomatic_complexity > 3);
         assert!(metrics.max_nesting_depth >= 2);
+        assert_eq!(functions.len(), 1);
+        assert!(functions[0].metrics.cyclomatic_complexity > 3);
+        assert!(functions[0].metrics.max_nesting_depth >= 2);
+    }
+
+    #[test]
+    fn test_boolean_operators_count_toward_complexity() {
+        let source = r#"
+def guarded(a, b, c):
+    if a and b or c:
+        return 1
+    return 0
+"#;
+        let (_metrics, functions) = RustAstAnalyzer::compute_metrics_static(source, &CognitiveRule::default_ruleset()).unwrap();
+        // 1 base + 1 `if` + 2 extra BoolOp operands (a-and-b, that-or-c)
+        assert_eq!(functions[0].metrics.cyclomatic_complexity, 4);
+    }
+
+    #[test]
+    fn test_ternary_and_comprehension_count_as_branches() {
+        let source = r#"
+def pick(items):
+    value = "a" if items else "b"
+    return [x for x in items if x > 0]
+"#;
+        let (_metrics, functions) = RustAstAnalyzer::compute_metrics_static(source, &CognitiveRule::default_ruleset()).unwrap();
+        // 1 base + 1 ternary + 1 comprehension `for` + 1 comprehension `if`
+        assert_eq!(functions[0].metrics.cyclomatic_complexity, 4);
+    }
+
+    #[test]
+    fn test_nested_function_is_its_own_entry() {
+        let source = r#"
+def outer():
+    def inner():
+        if True:
+            return 1
+    return inner()
+"#;
+        let (metrics, functions) = RustAstAnalyzer::compute_metrics_static(source, &CognitiveRule::default_ruleset()).unwrap();
+        assert_eq!(functions.len(), 2);
+        let outer = functions.iter().find(|f| f.name == "outer").unwrap();
+        let inner = functions.iter().find(|f| f.name == "inner").unwrap();
+        // The outer function's own body has no branches; the `if` belongs to `inner`.
+        assert_eq!(outer.metrics.cyclomatic_complexity, 1);
+        assert_eq!(inner.metrics.cyclomatic_complexity, 2);
+        // The file-wide aggregate still sees every branch in the file.
+        assert_eq!(metrics.cyclomatic_complexity, 2);
+    }
+
+    #[test]
+    fn test_class_def_gets_its_own_function_metrics_entry() {
+        let source = r#"
+class Greeter:
+    def hello(self):
+        if True:
+            return 1
+"#;
+        let (_metrics, functions) = RustAstAnalyzer::compute_metrics_static(source, &CognitiveRule::default_ruleset()).unwrap();
+        assert_eq!(functions.len(), 2);
+        let class_entry = functions.iter().find(|f| f.name == "Greeter").unwrap();
+        let method_entry = functions.iter().find(|f| f.name == "hello").unwrap();
+        // The class's own body is just the method def; the `if` belongs to `hello`.
+        assert_eq!(class_entry.metrics.cyclomatic_complexity, 1);
+        assert_eq!(method_entry.metrics.cyclomatic_complexity, 2);
+    }
+
+    #[test]
+    fn test_match_statement_counts_each_case_as_a_branch() {
+        let source = r#"
+def handle(command):
+    match command:
+        case "a":
+            return 1
+        case "b":
+            return 2
+        case _:
+            return 0
+"#;
+        let (_metrics, functions) = RustAstAnalyzer::compute_metrics_static(source, &CognitiveRule::default_ruleset()).unwrap();
+        // 1 base + 3 case arms
+        assert_eq!(functions[0].metrics.cyclomatic_complexity, 4);
+        // 3 case arms, each a structural point (no extra nesting increment
+        // since they're all at level 0).
+        assert_eq!(functions[0].metrics.cognitive_complexity, 3);
+    }
+
+    #[test]
+    fn test_analyze_source_returns_tuple_of_metrics_and_functions() {
+        Python::with_gil(|py| {
+            let analyzer = RustAstAnalyzer::new();
+            let source = "def f():\n    return 1\n";
+            let result = analyzer.analyze_source(py, source).unwrap();
+            let tuple = result.as_ref(py).downcast::<PyTuple>().unwrap();
+            assert_eq!(tuple.len(), 2);
+        });
+    }
+
+    #[test]
+    fn test_with_rules_changes_cognitive_complexity_through_compute_metrics() {
+        let source = "def f(x):\n    if x:\n        return 1\n    return 0\n";
+
+        let default_analyzer = RustAstAnalyzer::new();
+        let (default_metrics, default_functions) = default_analyzer.compute_metrics(source).unwrap();
+        assert_eq!(default_metrics.cognitive_complexity, 1);
+        assert_eq!(default_functions[0].metrics.cognitive_complexity, 1);
+
+        let tuned_analyzer = RustAstAnalyzer::with_rules(vec![CognitiveRule::new(
+            "if".to_string(),
+            "If".to_string(),
+            5.0,
+            true,
+            0.0,
+        )]);
+        let (tuned_metrics, tuned_functions) = tuned_analyzer.compute_metrics(source).unwrap();
+        assert_eq!(tuned_metrics.cognitive_complexity, 5);
+        assert_eq!(tuned_functions[0].metrics.cognitive_complexity, 5);
+    }
+
+    #[test]
+    fn test_cognitive_complexity_default_rules_nest_if_statements() {
+        let source = r#"
+def f(x):
+    if x:
+        if x > 1:
+            return 1
+    return 0
+"#;
+        let suite = ast::Suite::parse(source, "<module>").unwrap();
+        let line_index = LineIndex::new(source);
+        let rules = CognitiveRule::default_ruleset();
+        let mut walker = CognitiveWalker {
+            rules: &rules,
+            line_index: &line_index,
+            reports: Vec::new(),
+            descend_into_defs: true,
+        };
+        walker.visit_stmts(&suite, 0);
+
+        // Outer `if` scores 1 (level 0); inner `if` scores 1 + 1 (nesting
+        // increment for being one level deeper).
+        let total: f64 = walker.reports.iter().map(|r| r.score).sum();
+        assert_eq!(total, 3.0);
+        assert_eq!(walker.reports.len(), 2);
+        assert_eq!(walker.reports[0].score, 1.0);
+        assert_eq!(walker.reports[1].score, 2.0);
+        assert_eq!(walker.reports[1].nesting_level, 1);
+    }
+
+    #[test]
+    fn test_cognitive_complexity_reports_reason_and_node_kind() {
+        let source = "def f(x):\n    return 1 if x else 0\n";
+        let suite = ast::Suite::parse(source, "<module>").unwrap();
+        let line_index = LineIndex::new(source);
+        let rules = CognitiveRule::default_ruleset();
+        let mut walker = CognitiveWalker {
+            rules: &rules,
+            line_index: &line_index,
+            reports: Vec::new(),
+            descend_into_defs: true,
+        };
+        walker.visit_stmts(&suite, 0);
+
+        assert_eq!(walker.reports.len(), 1);
+        assert_eq!(walker.reports[0].reason, "ternary");
+        assert_eq!(walker.reports[0].node_kind, "IfExp");
+    }
+
+    #[test]
+    fn test_cognitive_complexity_with_custom_rules_tunes_weights() {
+        let source = "def f(x):\n    if x:\n        return 1\n    return 0\n";
+        let suite = ast::Suite::parse(source, "<module>").unwrap();
+        let line_index = LineIndex::new(source);
+        let rules = vec![CognitiveRule::new(
+            "if".to_string(),
+            "If".to_string(),
+            5.0,
+            true,
+            0.0,
+        )];
+        let mut walker = CognitiveWalker {
+            rules: &rules,
+            line_index: &line_index,
+            reports: Vec::new(),
+            descend_into_defs: true,
+        };
+        walker.visit_stmts(&suite, 0);
+
+        assert_eq!(walker.reports.len(), 1);
+        assert_eq!(walker.reports[0].score, 5.0);
+    }
+
+    #[test]
+    fn test_docstring_lines_are_source_not_comments() {
+        let source = "def f():\n    \"\"\"\n    docstring with for and if inside\n    \"\"\"\n    return 1\n";
+        let stats = classify_source_lines(source);
+        assert_eq!(stats.comment_lines, 0);
+        assert_eq!(stats.blank_lines, 0);
+        assert_eq!(stats.source_lines_of_code, 5);
+    }
+
+    #[test]
+    fn test_comment_and_blank_lines_classified_separately() {
+        let source = "# a full line comment\nx = 1\n\n# another comment\n";
+        let stats = classify_source_lines(source);
+        assert_eq!(stats.comment_lines, 2);
+        assert_eq!(stats.blank_lines, 1);
+        assert_eq!(stats.source_lines_of_code, 1);
+    }
+
+    #[test]
+    fn test_backslash_and_bracket_continuations_collapse_to_one_logical_line() {
+        let source = "x = 1 + \\\n    2\ny = (\n    1,\n    2,\n)\n";
+        let stats = classify_source_lines(source);
+        assert_eq!(stats.source_lines_of_code, 6);
+        assert_eq!(stats.logical_lines, 2);
+    }
+
+    #[test]
+    fn test_keyword_inside_string_literal_not_treated_as_comment() {
+        let source = "x = \"if something\"  # not a real branch\n";
+        let stats = classify_source_lines(source);
+        assert_eq!(stats.source_lines_of_code, 1);
+        assert_eq!(stats.comment_lines, 0);
+    }
+
+    #[test]
+    fn test_parse_blame_porcelain_counts_distinct_authors_and_time_range() {
+        let porcelain = concat!(
+            "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa 1 1 1\n",
+            "author Alice\n",
+            "author-mail <alice@example.com>\n",
+            "author-time 1000\n",
+            "author-tz +0000\n",
+            "committer Alice\n",
+            "committer-mail <alice@example.com>\n",
+            "committer-time 1000\n",
+            "committer-tz +0000\n",
+            "summary Initial commit\n",
+            "filename src/lib.rs\n",
+            "\tfn main() {}\n",
+            "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb 1 2 1\n",
+            "author Bob\n",
+            "author-mail <bob@example.com>\n",
+            "author-time 2000\n",
+            "author-tz +0000\n",
+            "committer Bob\n",
+            "committer-mail <bob@example.com>\n",
+            "committer-time 2000\n",
+            "committer-tz +0000\n",
+            "summary Second commit\n",
+            "filename src/lib.rs\n",
+            "\tfn helper() {}\n",
+        );
+
+        let (authors, first_time, last_time) = parse_blame_porcelain(porcelain);
+        assert_eq!(authors.len(), 2);
+        assert!(authors.contains("alice@example.com"));
+        assert!(authors.contains("bob@example.com"));
+        assert_eq!(first_time, 1000);
+        assert_eq!(last_time, 2000);
+    }
+
+    #[test]
+    fn test_hotspot_score_risk_formula() {
+        let risk = 4.0_f64 * (1.0_f64 + 9.0).ln();
+        let hotspot = HotspotScore {
+            file_path: "src/lib.rs".to_string(),
+            complexity: 4,
+            churn: 9,
+            author_count: 2,
+            first_commit_time: 1000,
+            last_commit_time: 2000,
+            risk: 4.0 * (1.0 + 9.0_f64).ln(),
+        };
+        assert_eq!(hotspot.risk, risk);
     }
 }
@@ -5,7 +5,8 @@
 use pyo3::prelude::*;
 use pyo3::types::{PyDict, PyList};
 use std::collections::HashMap;
-use regex::Regex;
+use regex::{Regex, RegexBuilder};
+use serde::Deserialize;
 
 /// A business logic pattern found in code
 #[derive(Debug, Clone)]
@@ -63,55 +64,134 @@ impl LogicCategory {
     }
 }
 
+/// A single detection rule: a name/category pair, a compiled regex, a
+/// confidence score, and whether the regex is matched against the whole
+/// file at once (for patterns that span line boundaries) rather than
+/// line-by-line.
+#[derive(Debug, Clone)]
+struct LogicRule {
+    name: String,
+    category: String,
+    regex: Regex,
+    confidence: f64,
+    multiline: bool,
+}
+
+/// On-disk shape of one rule entry in a TOML or JSON rule file
+#[derive(Debug, Deserialize)]
+struct RawRule {
+    name: String,
+    category: String,
+    pattern: String,
+    confidence: f64,
+    #[serde(default)]
+    multiline: bool,
+}
+
+/// Top-level shape of a rule file: `{ "rules": [ ... ] }` in JSON, or
+/// repeated `[[rules]]` tables in TOML.
+#[derive(Debug, Deserialize)]
+struct RawRuleFile {
+    rules: Vec<RawRule>,
+}
+
+impl LogicRule {
+    fn compile(raw: RawRule) -> Result<Self, String> {
+        let regex = if raw.multiline {
+            RegexBuilder::new(&raw.pattern)
+                .multi_line(true)
+                .dot_matches_new_line(true)
+                .build()
+        } else {
+            Regex::new(&raw.pattern)
+        }
+        .map_err(|e| format!("invalid pattern for rule '{}': {}", raw.name, e))?;
+
+        Ok(Self {
+            name: raw.name,
+            category: raw.category,
+            regex,
+            confidence: raw.confidence,
+            multiline: raw.multiline,
+        })
+    }
+}
+
 /// Business logic analyzer
 #[pyclass]
 pub struct RustBusinessLogicAnalyzer {
     patterns: Vec<BusinessLogicPattern>,
-    validation_patterns: Vec<Regex>,
-    auth_patterns: Vec<Regex>,
-    state_patterns: Vec<Regex>,
+    rules: Vec<LogicRule>,
 }
 
 #[pymethods]
 impl RustBusinessLogicAnalyzer {
     #[new]
     fn new() -> Self {
-        let validation_patterns = vec![
-            Regex::new(r"(?i)(validate|check|verify|ensure|require|assert)[A-Z]\w+").unwrap(),
-            Regex::new(r"(?i)(is_?valid|is_?empty|is_?null|has_?error)").unwrap(),
-            Regex::new(r"\.length\s*[<>=]+\s*\d+").unwrap(),
-            Regex::new(r"(?i)(email|phone|password|username).*validation").unwrap(),
-        ];
-
-        let auth_patterns = vec![
-            Regex::new(r"(?i)(login|logout|sign_?in|sign_?out|authenticate)").unwrap(),
-            Regex::new(r"(?i)(token|session|credentials|password|auth)").unwrap(),
-            Regex::new(r"(?i)(is_?authenticated|is_?logged_?in|has_?permission)").unwrap(),
-        ];
-
-        let state_patterns = vec![
-            Regex::new(r"(?i)(state|store|redux|mobx|riverpod)").unwrap(),
-            Regex::new(r"(?i)(get_?state|set_?state|update_?state)").unwrap(),
-            Regex::new(r"(?i)(observable|stream|subject|controller)").unwrap(),
-        ];
-
         Self {
             patterns: Vec::new(),
-            validation_patterns,
-            auth_patterns,
-            state_patterns,
+            rules: Self::default_rules(),
         }
     }
 
-    /// Analyze a source file for business logic
-    fn analyze_file(&mut self, file_path: String, source: String) -> PyResult<()> {
-        self.patterns.clear();
+    /// Register a single user-defined detection rule
+    #[pyo3(signature = (name, category, pattern, confidence, multiline = false))]
+    fn add_rule(
+        &mut self,
+        name: String,
+        category: String,
+        pattern: String,
+        confidence: f64,
+        multiline: bool,
+    ) -> PyResult<()> {
+        let raw = RawRule { name, category, pattern, confidence, multiline };
+        let rule = LogicRule::compile(raw)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e))?;
+        self.rules.push(rule);
+        Ok(())
+    }
+
+    /// Load additional rules from a `.toml` or `.json` rule file (format
+    /// chosen by extension), each entry shaped like
+    /// `{ name, category, pattern, confidence, multiline? }`. Returns the
+    /// number of rules added.
+    fn load_rules_from_file(&mut self, path: String) -> PyResult<usize> {
+        let content = std::fs::read_to_string(&path).map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to read rule file: {}", e))
+        })?;
+
+        let raw_rules = if path.ends_with(".toml") {
+            toml::from_str::<RawRuleFile>(&content)
+                .map_err(|e| {
+                    PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid TOML rule file: {}", e))
+                })?
+                .rules
+        } else if path.ends_with(".json") {
+            serde_json::from_str::<RawRuleFile>(&content)
+                .map_err(|e| {
+                    PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid JSON rule file: {}", e))
+                })?
+                .rules
+        } else {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Unsupported rule file extension for '{}', expected .toml or .json",
+                path
+            )));
+        };
 
-        // Split into lines for analysis
-        for (line_num, line) in source.lines().enumerate() {
-            self.analyze_line(&file_path, line_num + 1, line);
+        let added = raw_rules.len();
+        for raw in raw_rules {
+            let rule = LogicRule::compile(raw)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e))?;
+            self.rules.push(rule);
         }
 
+        Ok(added)
+    }
+
+    /// Analyze a source file for business logic
+    fn analyze_file(&mut self, file_path: String, source: String) -> PyResult<()> {
+        self.patterns = self.find_patterns(&file_path, &source);
         Ok(())
     }
 
@@ -180,73 +260,129 @@ impl RustBusinessLogicAnalyzer {
 
 // Implementation methods (not exposed to Python)
 impl RustBusinessLogicAnalyzer {
-    /// Analyze a single line of code
-    fn analyze_line(&mut self, file_path: &str, line_num: usize, line: &str) {
-        // Check for validation patterns
-        for pattern in &self.validation_patterns {
-            if let Some(mat) = pattern.find(line) {
-                self.patterns.push(BusinessLogicPattern {
-                    name: mat.as_str().to_string(),
-                    category: LogicCategory::Validation.as_str().to_string(),
-                    confidence: 0.8,
-                    file_path: file_path.to_string(),
-                    line_number: line_num,
-                    code_snippet: line.trim().to_string(),
-                });
+    /// The built-in rules, equivalent to the hardcoded regex sets this
+    /// analyzer shipped with before user-defined rules were supported.
+    fn default_rules() -> Vec<LogicRule> {
+        let specs: &[(&str, LogicCategory, f64)] = &[
+            (r"(?i)(validate|check|verify|ensure|require|assert)[A-Z]\w+", LogicCategory::Validation, 0.8),
+            (r"(?i)(is_?valid|is_?empty|is_?null|has_?error)", LogicCategory::Validation, 0.8),
+            (r"\.length\s*[<>=]+\s*\d+", LogicCategory::Validation, 0.8),
+            (r"(?i)(email|phone|password|username).*validation", LogicCategory::Validation, 0.8),
+            (r"(?i)(login|logout|sign_?in|sign_?out|authenticate)", LogicCategory::Authentication, 0.85),
+            (r"(?i)(token|session|credentials|password|auth)", LogicCategory::Authentication, 0.85),
+            (r"(?i)(is_?authenticated|is_?logged_?in|has_?permission)", LogicCategory::Authentication, 0.85),
+            (r"(?i)(state|store|redux|mobx|riverpod)", LogicCategory::StateManagement, 0.75),
+            (r"(?i)(get_?state|set_?state|update_?state)", LogicCategory::StateManagement, 0.75),
+            (r"(?i)(observable|stream|subject|controller)", LogicCategory::StateManagement, 0.75),
+            (r"(?i)\b(try|catch|except)\b", LogicCategory::ErrorHandling, 0.9),
+            (r"(?i)\b(fetch|request|api)\b", LogicCategory::Integration, 0.7),
+        ];
+
+        specs
+            .iter()
+            .map(|(pattern, category, confidence)| LogicRule {
+                name: category.as_str().to_string(),
+                category: category.as_str().to_string(),
+                regex: Regex::new(pattern).unwrap(),
+                confidence: *confidence,
+                multiline: false,
+            })
+            .collect()
+    }
+
+    /// Byte offset of the start of each line in `source` (index 0 is line 1)
+    fn line_starts(source: &str) -> Vec<usize> {
+        let mut starts = vec![0usize];
+        for (i, byte) in source.bytes().enumerate() {
+            if byte == b'\n' {
+                starts.push(i + 1);
             }
         }
+        starts
+    }
 
-        // Check for authentication patterns
-        for pattern in &self.auth_patterns {
-            if let Some(mat) = pattern.find(line) {
-                self.patterns.push(BusinessLogicPattern {
-                    name: mat.as_str().to_string(),
-                    category: LogicCategory::Authentication.as_str().to_string(),
-                    confidence: 0.85,
-                    file_path: file_path.to_string(),
-                    line_number: line_num,
-                    code_snippet: line.trim().to_string(),
-                });
+    /// 1-indexed line number containing byte offset `offset`
+    fn line_number_for_offset(line_starts: &[usize], offset: usize) -> usize {
+        line_starts.partition_point(|&start| start <= offset)
+    }
+
+    /// Run every rule over `source` and return the matches, deduplicated so
+    /// that when several rules hit the same byte span only the
+    /// highest-confidence match is kept.
+    fn find_patterns(&self, file_path: &str, source: &str) -> Vec<BusinessLogicPattern> {
+        let line_starts = Self::line_starts(source);
+        let mut best: HashMap<(usize, usize), BusinessLogicPattern> = HashMap::new();
+
+        for (line_idx, line) in source.lines().enumerate() {
+            let line_start = line_starts[line_idx];
+            for rule in self.rules.iter().filter(|r| !r.multiline) {
+                if let Some(mat) = rule.regex.find(line) {
+                    Self::record_match(
+                        &mut best,
+                        rule,
+                        file_path,
+                        line_idx + 1,
+                        line.trim(),
+                        line_start + mat.start(),
+                        line_start + mat.end(),
+                        mat.as_str(),
+                    );
+                }
             }
         }
 
-        // Check for state management patterns
-        for pattern in &self.state_patterns {
-            if let Some(mat) = pattern.find(line) {
-                self.patterns.push(BusinessLogicPattern {
-                    name: mat.as_str().to_string(),
-                    category: LogicCategory::StateManagement.as_str().to_string(),
-                    confidence: 0.75,
-                    file_path: file_path.to_string(),
-                    line_number: line_num,
-                    code_snippet: line.trim().to_string(),
-                });
+        // Rules that need to see across line boundaries are matched against
+        // the whole file instead of line-by-line.
+        for rule in self.rules.iter().filter(|r| r.multiline) {
+            for mat in rule.regex.find_iter(source) {
+                let line_num = Self::line_number_for_offset(&line_starts, mat.start());
+                let snippet = source.lines().nth(line_num - 1).unwrap_or("").trim().to_string();
+                Self::record_match(
+                    &mut best,
+                    rule,
+                    file_path,
+                    line_num,
+                    &snippet,
+                    mat.start(),
+                    mat.end(),
+                    mat.as_str(),
+                );
             }
         }
 
-        // Check for error handling
-        if line.contains("try") || line.contains("catch") || line.contains("except") {
-            self.patterns.push(BusinessLogicPattern {
-                name: "ErrorHandling".to_string(),
-                category: LogicCategory::ErrorHandling.as_str().to_string(),
-                confidence: 0.9,
-                file_path: file_path.to_string(),
-                line_number: line_num,
-                code_snippet: line.trim().to_string(),
-            });
-        }
+        let mut patterns: Vec<BusinessLogicPattern> = best.into_values().collect();
+        patterns.sort_by(|a, b| a.line_number.cmp(&b.line_number).then_with(|| a.name.cmp(&b.name)));
+        patterns
+    }
 
-        // Check for API integration
-        if line.contains("fetch") || line.contains("request") || line.contains("api") {
-            self.patterns.push(BusinessLogicPattern {
-                name: "APIIntegration".to_string(),
-                category: LogicCategory::Integration.as_str().to_string(),
-                confidence: 0.7,
-                file_path: file_path.to_string(),
-                line_number: line_num,
-                code_snippet: line.trim().to_string(),
-            });
-        }
+    /// Record a single rule match, keeping only the highest-confidence
+    /// pattern for any given `(start, end)` byte span.
+    fn record_match(
+        best: &mut HashMap<(usize, usize), BusinessLogicPattern>,
+        rule: &LogicRule,
+        file_path: &str,
+        line_number: usize,
+        snippet: &str,
+        start: usize,
+        end: usize,
+        matched_text: &str,
+    ) {
+        let pattern = BusinessLogicPattern {
+            name: matched_text.to_string(),
+            category: rule.category.clone(),
+            confidence: rule.confidence,
+            file_path: file_path.to_string(),
+            line_number,
+            code_snippet: snippet.to_string(),
+        };
+
+        best.entry((start, end))
+            .and_modify(|existing| {
+                if pattern.confidence > existing.confidence {
+                    *existing = pattern.clone();
+                }
+            })
+            .or_insert(pattern);
     }
 }
 
@@ -290,7 +426,7 @@ mod tests {
     #[test]
     fn test_error_handling_detection() {
         let mut analyzer = RustBusinessLogicAnalyzer::new();
-        
+
         let source = r#"
             try:
                 risky_operation()
@@ -299,8 +435,70 @@ mod tests {
         "#;
 
         analyzer.analyze_file("test.py".to_string(), source.to_string()).unwrap();
-        
+
         assert!(analyzer.len() > 0);
         assert!(analyzer.patterns.iter().any(|p| p.category == "ErrorHandling"));
     }
+
+    #[test]
+    fn test_error_handling_detection_requires_word_boundary() {
+        let mut analyzer = RustBusinessLogicAnalyzer::new();
+
+        // "trying" and "catching" merely contain "try"/"catch" as
+        // substrings and must not be mistaken for error-handling code.
+        let source = "let trying = plan_meeting();\nlet catching = \"misc\";";
+
+        analyzer.analyze_file("test.py".to_string(), source.to_string()).unwrap();
+
+        assert!(!analyzer.patterns.iter().any(|p| p.category == "ErrorHandling"));
+    }
+
+    #[test]
+    fn test_integration_detection() {
+        let mut analyzer = RustBusinessLogicAnalyzer::new();
+
+        let source = "const response = await fetch(api_url);";
+
+        analyzer.analyze_file("test.py".to_string(), source.to_string()).unwrap();
+
+        assert!(analyzer.patterns.iter().any(|p| p.category == "Integration"));
+    }
+
+    #[test]
+    fn test_add_rule_detects_custom_pattern() {
+        let mut analyzer = RustBusinessLogicAnalyzer::new();
+        analyzer
+            .add_rule("PII".to_string(), "Compliance".to_string(), r"(?i)ssn|social_security".to_string(), 0.95, false)
+            .unwrap();
+
+        analyzer.analyze_file("test.py".to_string(), "ssn = get_ssn(user)".to_string()).unwrap();
+
+        assert!(analyzer.patterns.iter().any(|p| p.category == "Compliance"));
+    }
+
+    #[test]
+    fn test_add_rule_rejects_invalid_regex() {
+        let mut analyzer = RustBusinessLogicAnalyzer::new();
+        let result = analyzer.add_rule("Bad".to_string(), "Compliance".to_string(), "(unclosed".to_string(), 0.5, false);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_overlapping_rules_keep_highest_confidence_only() {
+        let mut analyzer = RustBusinessLogicAnalyzer::new();
+        // Overlaps the built-in Authentication rule matching "token" on the same span.
+        analyzer
+            .add_rule("HighConfidenceAuth".to_string(), "Compliance".to_string(), "token".to_string(), 0.99, false)
+            .unwrap();
+
+        analyzer.analyze_file("test.py".to_string(), "let token = refresh_token();".to_string()).unwrap();
+
+        let matches_at_token: Vec<_> = analyzer
+            .patterns
+            .iter()
+            .filter(|p| p.name == "token")
+            .collect();
+        assert_eq!(matches_at_token.len(), 1);
+        assert_eq!(matches_at_token[0].category, "Compliance");
+    }
 }
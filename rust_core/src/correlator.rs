@@ -10,6 +10,7 @@
 use pyo3::prelude::*;
 use pyo3::types::{PyDict, PyList};
 use std::collections::HashMap;
+use std::str::FromStr;
 use serde::{Serialize, Deserialize};
 
 /// Event types that can be correlated
@@ -28,6 +29,18 @@ pub enum EventType {
     ScreenChange,
 }
 
+/// Method used to score similarity between the `data` payloads of two events
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[pyclass]
+pub enum CorrelationMethod {
+    /// Exact string equality on shared keys (the original behavior)
+    Exact,
+    /// Pearson correlation coefficient over shared numeric keys
+    Pearson,
+    /// Spearman rank correlation coefficient over shared numeric keys
+    Spearman,
+}
+
 /// A single event in the timeline
 #[derive(Debug, Clone)]
 #[pyclass]
@@ -80,6 +93,9 @@ pub struct Correlation {
     pub time_delta_ms: f64,
     #[pyo3(get)]
     pub correlation_type: String,
+    /// The join key that short-circuited this match, if any
+    #[pyo3(get)]
+    pub matched_key: Option<String>,
 }
 
 #[pymethods]
@@ -96,24 +112,187 @@ impl Correlation {
     }
 }
 
+/// A complete multi-hop path through the correlation graph, e.g.
+/// `UI_INTERACTION -> API_CALL -> API_RESPONSE -> NAVIGATION`
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct CausalChain {
+    #[pyo3(get)]
+    pub event_ids: Vec<String>,
+    #[pyo3(get)]
+    pub confidence: f64,
+    #[pyo3(get)]
+    pub total_time_ms: f64,
+}
+
+#[pymethods]
+impl CausalChain {
+    fn __repr__(&self) -> String {
+        format!(
+            "CausalChain({}, confidence={:.2}, total_time={:.0}ms)",
+            self.event_ids.join(" → "),
+            self.confidence,
+            self.total_time_ms
+        )
+    }
+}
+
+/// A contiguous run of events with no gap larger than the configured
+/// `idle_gap_ms`, i.e. one discrete user journey within a longer recording.
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct Session {
+    #[pyo3(get)]
+    pub start_time: f64,
+    #[pyo3(get)]
+    pub end_time: f64,
+    #[pyo3(get)]
+    pub event_count: usize,
+    correlations: Vec<Correlation>,
+}
+
+#[pymethods]
+impl Session {
+    /// Correlations whose source and target events both fall inside this session
+    fn get_correlations(&self, py: Python) -> PyResult<PyObject> {
+        let py_list = PyList::empty(py);
+        for corr in &self.correlations {
+            py_list.append(Py::new(py, corr.clone())?)?;
+        }
+        Ok(py_list.into())
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Session(start={:.0}, end={:.0}, events={}, correlations={})",
+            self.start_time,
+            self.end_time,
+            self.event_count,
+            self.correlations.len()
+        )
+    }
+}
+
+/// A user-defined rule describing when two event types should be correlated
+/// and how confidence should be weighted for that specific pair. Registering
+/// rules on a `RustCorrelator` replaces the built-in `should_correlate`
+/// type-pair list with a data-driven lookup.
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct CorrelationRule {
+    #[pyo3(get, set)]
+    pub source_type: String,
+    #[pyo3(get, set)]
+    pub target_type: String,
+    #[pyo3(get, set)]
+    pub max_time_delta_ms: f64,
+    #[pyo3(get, set)]
+    pub min_confidence: f64,
+    #[pyo3(get, set)]
+    pub time_weight: f64,
+    #[pyo3(get, set)]
+    pub type_weight: f64,
+    #[pyo3(get, set)]
+    pub data_weight: f64,
+}
+
+#[pymethods]
+impl CorrelationRule {
+    #[new]
+    #[pyo3(signature = (
+        source_type,
+        target_type,
+        max_time_delta_ms=5000.0,
+        min_confidence=0.5,
+        time_weight=0.4,
+        type_weight=0.3,
+        data_weight=0.3
+    ))]
+    fn new(
+        source_type: String,
+        target_type: String,
+        max_time_delta_ms: f64,
+        min_confidence: f64,
+        time_weight: f64,
+        type_weight: f64,
+        data_weight: f64,
+    ) -> Self {
+        Self {
+            source_type,
+            target_type,
+            max_time_delta_ms,
+            min_confidence,
+            time_weight,
+            type_weight,
+            data_weight,
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "CorrelationRule({}→{}, window={:.0}ms, min_confidence={:.2})",
+            self.source_type, self.target_type, self.max_time_delta_ms, self.min_confidence
+        )
+    }
+}
+
+/// Caps on `dfs_causal_chains`'s path reconstruction; see that method's
+/// doc comment for why `on_path`'s cycle guard alone isn't enough.
+const MAX_CAUSAL_CHAIN_DEPTH: usize = 50;
+const MAX_CAUSAL_CHAINS: usize = 10_000;
+
 /// High-performance event correlator
 #[pyclass]
 pub struct RustCorrelator {
     events: Vec<Event>,
     max_time_delta_ms: f64,
     min_confidence: f64,
+    correlation_method: CorrelationMethod,
+    rules: Vec<CorrelationRule>,
+    join_keys: Vec<String>,
 }
 
 #[pymethods]
 impl RustCorrelator {
     #[new]
     #[pyo3(signature = (max_time_delta_ms=5000.0, min_confidence=0.5))]
-    fn new(max_time_delta_ms: f64, min_confidence: f64) -> Self {
-        Self {
+    fn new(max_time_delta_ms: f64, min_confidence: f64) -> PyResult<Self> {
+        if max_time_delta_ms < 0.0 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "max_time_delta_ms must be >= 0.0, got {}",
+                max_time_delta_ms
+            )));
+        }
+        Ok(Self {
             events: Vec::new(),
             max_time_delta_ms,
             min_confidence,
-        }
+            correlation_method: CorrelationMethod::Exact,
+            rules: Vec::new(),
+            join_keys: Vec::new(),
+        })
+    }
+
+    /// Set the method used to score similarity between shared `data` keys
+    fn set_correlation_method(&mut self, method: CorrelationMethod) {
+        self.correlation_method = method;
+    }
+
+    /// Register a correlation rule, overriding the built-in type-pair list
+    fn add_rule(&mut self, rule: CorrelationRule) {
+        self.rules.push(rule);
+    }
+
+    /// Remove all registered rules, reverting to the built-in type-pair list
+    fn clear_rules(&mut self) {
+        self.rules.clear();
+    }
+
+    /// Declare `data` keys (e.g. a request/trace id) that deterministically
+    /// link events regardless of how they'd otherwise score. Two events
+    /// sharing a value for any of these keys correlate with confidence 1.0.
+    fn set_join_keys(&mut self, keys: Vec<String>) {
+        self.join_keys = keys;
     }
 
     /// Add an event to the timeline
@@ -155,91 +334,35 @@ impl RustCorrelator {
 
     /// Correlate UI interactions with API calls
     fn correlate_ui_to_api(&self, py: Python) -> PyResult<PyObject> {
-        let mut correlations = Vec::new();
-
-        // Find UI → API correlations
-        for ui_event in &self.events {
-            if ui_event.event_type != "UI_INTERACTION" {
-                continue;
-            }
-
-            // Look for API calls within time window
-            for api_event in &self.events {
-                if api_event.event_type != "API_CALL" {
-                    continue;
-                }
-
-                let time_delta = api_event.timestamp - ui_event.timestamp;
-                
-                // Skip if outside time window or in wrong order
-                if time_delta < 0.0 || time_delta > self.max_time_delta_ms {
-                    continue;
-                }
-
-                // Calculate confidence based on timing and context
-                let confidence = self.calculate_confidence(ui_event, api_event, time_delta);
-
-                if confidence >= self.min_confidence {
-                    correlations.push(Correlation {
-                        source_event_id: ui_event.event_id.clone(),
-                        target_event_id: api_event.event_id.clone(),
-                        confidence,
-                        time_delta_ms: time_delta,
-                        correlation_type: "UI_TO_API".to_string(),
-                    });
-                }
-            }
-        }
+        let correlations = self.correlate_windowed(
+            |source_type, target_type| source_type == "UI_INTERACTION" && target_type == "API_CALL",
+            |_, _| "UI_TO_API".to_string(),
+        );
 
         // Convert to Python list
         let py_list = PyList::empty(py);
         for corr in correlations {
             py_list.append(Py::new(py, corr)?)?;
         }
-        
+
         Ok(py_list.into())
     }
 
     /// Correlate API responses with navigation changes
     fn correlate_api_to_navigation(&self, py: Python) -> PyResult<PyObject> {
-        let mut correlations = Vec::new();
-
-        for api_event in &self.events {
-            if api_event.event_type != "API_RESPONSE" {
-                continue;
-            }
-
-            // Look for navigation changes after API response
-            for nav_event in &self.events {
-                if nav_event.event_type != "NAVIGATION" && nav_event.event_type != "SCREEN_CHANGE" {
-                    continue;
-                }
-
-                let time_delta = nav_event.timestamp - api_event.timestamp;
-                
-                if time_delta < 0.0 || time_delta > self.max_time_delta_ms {
-                    continue;
-                }
-
-                let confidence = self.calculate_confidence(api_event, nav_event, time_delta);
-
-                if confidence >= self.min_confidence {
-                    correlations.push(Correlation {
-                        source_event_id: api_event.event_id.clone(),
-                        target_event_id: nav_event.event_id.clone(),
-                        confidence,
-                        time_delta_ms: time_delta,
-                        correlation_type: "API_TO_NAVIGATION".to_string(),
-                    });
-                }
-            }
-        }
+        let correlations = self.correlate_windowed(
+            |source_type, target_type| {
+                source_type == "API_RESPONSE"
+                    && (target_type == "NAVIGATION" || target_type == "SCREEN_CHANGE")
+            },
+            |_, _| "API_TO_NAVIGATION".to_string(),
+        );
 
         let py_list = PyList::empty(py);
         for corr in correlations {
             py_list.append(Py::new(py, corr)?)?;
         }
-        
+
         Ok(py_list.into())
     }
 
@@ -270,17 +393,49 @@ impl RustCorrelator {
         Ok(py_dict.into())
     }
 
-    /// Get correlation statistics
-    fn get_statistics(&self, py: Python) -> PyResult<PyObject> {
+    /// Walk the correlation graph to reconstruct complete end-to-end causal
+    /// chains (e.g. UI tap -> API call -> API response -> navigation)
+    /// instead of the one-hop adjacency list `build_correlation_graph`
+    /// exposes. Chains are sorted by aggregate confidence, descending.
+    fn find_causal_chains(&self, py: Python) -> PyResult<PyObject> {
+        let chains = self.causal_chains();
+
+        let py_list = PyList::empty(py);
+        for chain in chains {
+            py_list.append(Py::new(py, chain)?)?;
+        }
+
+        Ok(py_list.into())
+    }
+
+    /// Partition the timeline into sessions wherever the gap between
+    /// consecutive valid events exceeds `idle_gap_ms`. NaN/+-Infinity
+    /// timestamped events are excluded, the same as the correlation window.
+    fn segment_sessions(&self, py: Python, idle_gap_ms: f64) -> PyResult<PyObject> {
+        let sessions = self.compute_sessions(idle_gap_ms);
+
+        let py_list = PyList::empty(py);
+        for session in sessions {
+            py_list.append(Py::new(py, session)?)?;
+        }
+
+        Ok(py_list.into())
+    }
+
+    /// Get correlation statistics. When `idle_gap_ms` is given, also segment
+    /// the timeline into sessions and report a per-session breakdown
+    /// alongside the global aggregate.
+    #[pyo3(signature = (idle_gap_ms=None))]
+    fn get_statistics(&self, py: Python, idle_gap_ms: Option<f64>) -> PyResult<PyObject> {
         let correlations = self.correlate_events();
-        
+
         let total = correlations.len();
         let avg_confidence = if total > 0 {
             correlations.iter().map(|c| c.confidence).sum::<f64>() / total as f64
         } else {
             0.0
         };
-        
+
         let avg_time_delta = if total > 0 {
             correlations.iter().map(|c| c.time_delta_ms).sum::<f64>() / total as f64
         } else {
@@ -298,59 +453,411 @@ impl RustCorrelator {
         stats.set_item("total_correlations", total)?;
         stats.set_item("avg_confidence", avg_confidence)?;
         stats.set_item("avg_time_delta_ms", avg_time_delta)?;
-        
+
         let by_type_dict = PyDict::new(py);
         for (corr_type, count) in by_type {
             by_type_dict.set_item(corr_type, count)?;
         }
         stats.set_item("by_type", by_type_dict)?;
 
+        if let Some(idle_gap_ms) = idle_gap_ms {
+            let sessions = self.compute_sessions(idle_gap_ms);
+            let sessions_list = PyList::empty(py);
+            for (index, session) in sessions.iter().enumerate() {
+                let session_total = session.correlations.len();
+                let session_avg_confidence = if session_total > 0 {
+                    session.correlations.iter().map(|c| c.confidence).sum::<f64>() / session_total as f64
+                } else {
+                    0.0
+                };
+
+                let session_stats = PyDict::new(py);
+                session_stats.set_item("session_index", index)?;
+                session_stats.set_item("start_time", session.start_time)?;
+                session_stats.set_item("end_time", session.end_time)?;
+                session_stats.set_item("event_count", session.event_count)?;
+                session_stats.set_item("total_correlations", session_total)?;
+                session_stats.set_item("avg_confidence", session_avg_confidence)?;
+                sessions_list.append(session_stats)?;
+            }
+            stats.set_item("sessions", sessions_list)?;
+        }
+
         Ok(stats.into())
     }
 }
 
 // Implementation methods (not exposed to Python)
 impl RustCorrelator {
-    /// Internal: correlate all events
+    /// Internal: partition the valid (finite-timestamp) timeline into
+    /// sessions separated by gaps larger than `idle_gap_ms`, attaching each
+    /// session the subset of correlations whose endpoints both fall inside it.
+    fn compute_sessions(&self, idle_gap_ms: f64) -> Vec<Session> {
+        let sorted_events = self.sorted_valid_events();
+        if sorted_events.is_empty() {
+            return Vec::new();
+        }
+
+        let correlations = self.correlate_events();
+
+        let mut ranges: Vec<(f64, f64, Vec<String>)> = Vec::new();
+        let mut current_start = sorted_events[0].timestamp;
+        let mut current_end = sorted_events[0].timestamp;
+        let mut current_ids = vec![sorted_events[0].event_id.clone()];
+
+        for event in sorted_events.iter().skip(1) {
+            if event.timestamp - current_end > idle_gap_ms {
+                ranges.push((current_start, current_end, std::mem::take(&mut current_ids)));
+                current_start = event.timestamp;
+            }
+            current_end = event.timestamp;
+            current_ids.push(event.event_id.clone());
+        }
+        ranges.push((current_start, current_end, current_ids));
+
+        ranges
+            .into_iter()
+            .map(|(start_time, end_time, ids)| {
+                let id_set: std::collections::HashSet<&str> = ids.iter().map(|s| s.as_str()).collect();
+                let session_correlations: Vec<Correlation> = correlations
+                    .iter()
+                    .filter(|c| {
+                        id_set.contains(c.source_event_id.as_str())
+                            && id_set.contains(c.target_event_id.as_str())
+                    })
+                    .cloned()
+                    .collect();
+
+                Session {
+                    start_time,
+                    end_time,
+                    event_count: ids.len(),
+                    correlations: session_correlations,
+                }
+            })
+            .collect()
+    }
+
+    /// Internal: reconstruct maximal causal chains from the correlation
+    /// graph, walking every maximal path from each source node that has no
+    /// incoming edge.
+    fn causal_chains(&self) -> Vec<CausalChain> {
+        let correlations = self.correlate_events();
+
+        let mut graph: HashMap<String, Vec<(String, f64)>> = HashMap::new();
+        let mut has_incoming: std::collections::HashSet<String> = std::collections::HashSet::new();
+        let mut nodes: std::collections::HashSet<String> = std::collections::HashSet::new();
+
+        for corr in &correlations {
+            graph
+                .entry(corr.source_event_id.clone())
+                .or_insert_with(Vec::new)
+                .push((corr.target_event_id.clone(), corr.confidence));
+            has_incoming.insert(corr.target_event_id.clone());
+            nodes.insert(corr.source_event_id.clone());
+            nodes.insert(corr.target_event_id.clone());
+        }
+
+        let timestamps: HashMap<String, f64> = self
+            .events
+            .iter()
+            .map(|e| (e.event_id.clone(), e.timestamp))
+            .collect();
+
+        let mut raw_chains: Vec<(Vec<String>, f64)> = Vec::new();
+        let mut roots: Vec<&String> = nodes.iter().filter(|n| !has_incoming.contains(*n)).collect();
+        roots.sort();
+
+        for root in roots {
+            let mut on_path: std::collections::HashSet<String> = std::collections::HashSet::new();
+            self.dfs_causal_chains(root, &graph, &mut on_path, vec![root.clone()], 1.0, &mut raw_chains);
+        }
+
+        let mut chains: Vec<CausalChain> = raw_chains
+            .into_iter()
+            .map(|(event_ids, confidence)| {
+                let total_time_ms = match (event_ids.first(), event_ids.last()) {
+                    (Some(first), Some(last)) => {
+                        timestamps.get(last).copied().unwrap_or(0.0)
+                            - timestamps.get(first).copied().unwrap_or(0.0)
+                    }
+                    _ => 0.0,
+                };
+                CausalChain { event_ids, confidence, total_time_ms }
+            })
+            .collect();
+
+        chains.sort_by(|a, b| b.confidence.partial_cmp(&a.confidence).unwrap_or(std::cmp::Ordering::Equal));
+
+        chains
+    }
+
+    /// DFS over the correlation graph, tracking nodes visited on the
+    /// current path so a cycle (e.g. API_CALL <-> API_RESPONSE) can't
+    /// recurse forever. Each maximal path (a node with no unvisited
+    /// outgoing edge) is emitted with the product of its edge confidences.
+    ///
+    /// `on_path` only rules out cycles: a dense graph with many fan-outs can
+    /// still have an exponential number of simple paths, so this is also
+    /// bounded by `MAX_CAUSAL_CHAIN_DEPTH` (a chain stops extending and is
+    /// emitted as-is once it gets this long) and `MAX_CAUSAL_CHAINS` (a
+    /// global cap on `out`, checked on every call since `out` is shared
+    /// across the whole DFS, including sibling root traversals).
+    fn dfs_causal_chains(
+        &self,
+        node: &str,
+        graph: &HashMap<String, Vec<(String, f64)>>,
+        on_path: &mut std::collections::HashSet<String>,
+        path: Vec<String>,
+        confidence: f64,
+        out: &mut Vec<(Vec<String>, f64)>,
+    ) {
+        if out.len() >= MAX_CAUSAL_CHAINS {
+            return;
+        }
+
+        on_path.insert(node.to_string());
+
+        let mut extended = false;
+        if path.len() < MAX_CAUSAL_CHAIN_DEPTH {
+            if let Some(neighbors) = graph.get(node) {
+                for (next, edge_confidence) in neighbors {
+                    if out.len() >= MAX_CAUSAL_CHAINS {
+                        break;
+                    }
+                    if on_path.contains(next) {
+                        continue;
+                    }
+                    extended = true;
+                    let mut next_path = path.clone();
+                    next_path.push(next.clone());
+                    self.dfs_causal_chains(next, graph, on_path, next_path, confidence * edge_confidence, out);
+                }
+            }
+        }
+
+        if !extended {
+            out.push((path, confidence));
+        }
+
+        on_path.remove(node);
+    }
+
+    /// Internal: correlate all events, consulting the user-defined rule
+    /// table when one has been registered and otherwise falling back to
+    /// the built-in `should_correlate` type-pair list. Join-key matches
+    /// (if any join keys are configured) always take precedence over the
+    /// heuristic result for the same event pair.
     fn correlate_events(&self) -> Vec<Correlation> {
-        let mut correlations = Vec::new();
+        let heuristic = if self.rules.is_empty() {
+            self.correlate_windowed(
+                |source_type, target_type| self.should_correlate(source_type, target_type),
+                |source_type, target_type| {
+                    format!(
+                        "{}_{}",
+                        self.normalize_event_type(source_type),
+                        self.normalize_event_type(target_type)
+                    )
+                },
+            )
+        } else {
+            self.correlate_with_rules()
+        };
+
+        if self.join_keys.is_empty() {
+            return heuristic;
+        }
+
+        let mut by_pair: HashMap<(String, String), Correlation> = heuristic
+            .into_iter()
+            .map(|c| ((c.source_event_id.clone(), c.target_event_id.clone()), c))
+            .collect();
+
+        for join_match in self.correlate_by_join_keys() {
+            by_pair.insert(
+                (join_match.source_event_id.clone(), join_match.target_event_id.clone()),
+                join_match,
+            );
+        }
 
-        // Sort events by timestamp for efficient correlation
-        let mut sorted_events = self.events.clone();
-        sorted_events.sort_by(|a, b| {
-            // Handle NaN timestamps gracefully - treat NaN as greater than any value
-            a.timestamp.partial_cmp(&b.timestamp).unwrap_or(std::cmp::Ordering::Greater)
+        let mut correlations: Vec<Correlation> = by_pair.into_values().collect();
+        correlations.sort_by(|a, b| {
+            (&a.source_event_id, &a.target_event_id).cmp(&(&b.source_event_id, &b.target_event_id))
         });
 
-        // Correlate each event with subsequent events
-        for (i, source) in sorted_events.iter().enumerate() {
-            for target in sorted_events.iter().skip(i + 1) {
+        correlations
+    }
+
+    /// Find, for every pair of events sharing a value on one of the
+    /// configured join keys, a deterministic correlation with confidence
+    /// 1.0 - independent of the time window used by the heuristic path.
+    fn correlate_by_join_keys(&self) -> Vec<Correlation> {
+        let mut correlations = Vec::new();
+
+        for key in &self.join_keys {
+            let mut groups: HashMap<&String, Vec<&Event>> = HashMap::new();
+            for event in &self.events {
+                if let Some(value) = event.data.get(key) {
+                    groups.entry(value).or_insert_with(Vec::new).push(event);
+                }
+            }
+
+            for members in groups.values() {
+                if members.len() < 2 {
+                    continue;
+                }
+                for (i, source) in members.iter().enumerate() {
+                    for target in members.iter().skip(i + 1) {
+                        // Keep a stable source/target order: by timestamp
+                        // when both are comparable, otherwise by event id.
+                        let (source, target) = if source.timestamp.is_finite()
+                            && target.timestamp.is_finite()
+                            && target.timestamp < source.timestamp
+                        {
+                            (*target, *source)
+                        } else {
+                            (*source, *target)
+                        };
+
+                        correlations.push(Correlation {
+                            source_event_id: source.event_id.clone(),
+                            target_event_id: target.event_id.clone(),
+                            confidence: 1.0,
+                            time_delta_ms: target.timestamp - source.timestamp,
+                            correlation_type: "JOIN_KEY".to_string(),
+                            matched_key: Some(key.clone()),
+                        });
+                    }
+                }
+            }
+        }
+
+        correlations
+    }
+
+    /// Find the registered rule for a given source/target type pair
+    fn find_rule(&self, source_type: &str, target_type: &str) -> Option<&CorrelationRule> {
+        self.rules
+            .iter()
+            .find(|r| r.source_type == source_type && r.target_type == target_type)
+    }
+
+    /// Correlate using the registered rule table: each rule supplies its own
+    /// time window, confidence threshold, and weight overrides.
+    fn correlate_with_rules(&self) -> Vec<Correlation> {
+        let mut correlations = Vec::new();
+        let sorted_events = self.sorted_valid_events();
+
+        // The sliding window must be wide enough for the loosest rule so
+        // that no eligible pair is dropped before its rule is consulted.
+        let widest_window = self
+            .rules
+            .iter()
+            .map(|r| r.max_time_delta_ms)
+            .fold(0.0_f64, f64::max);
+
+        let mut left = 0usize;
+        for right in 0..sorted_events.len() {
+            let target = &sorted_events[right];
+
+            while target.timestamp - sorted_events[left].timestamp > widest_window {
+                left += 1;
+            }
+
+            for source in &sorted_events[left..right] {
+                let Some(rule) = self.find_rule(&source.event_type, &target.event_type) else {
+                    continue;
+                };
+
                 let time_delta = target.timestamp - source.timestamp;
+                if time_delta > rule.max_time_delta_ms {
+                    continue;
+                }
 
-                // Stop if we're outside the time window
-                if time_delta > self.max_time_delta_ms {
-                    break;
+                let confidence = self.calculate_confidence_weighted(
+                    source,
+                    target,
+                    time_delta,
+                    rule.max_time_delta_ms,
+                    rule.time_weight,
+                    rule.type_weight,
+                    rule.data_weight,
+                );
+
+                if confidence >= rule.min_confidence {
+                    correlations.push(Correlation {
+                        source_event_id: source.event_id.clone(),
+                        target_event_id: target.event_id.clone(),
+                        confidence,
+                        time_delta_ms: time_delta,
+                        correlation_type: format!(
+                            "{}_{}",
+                            self.normalize_event_type(&source.event_type),
+                            self.normalize_event_type(&target.event_type)
+                        ),
+                        matched_key: None,
+                    });
                 }
+            }
+        }
 
-                // Check if these event types should be correlated
-                if !self.should_correlate(&source.event_type, &target.event_type) {
+        correlations
+    }
+
+    /// Sort events ascending by timestamp, excluding any whose timestamp is
+    /// NaN or +/-Infinity so they can never enter a correlation window.
+    fn sorted_valid_events(&self) -> Vec<Event> {
+        let mut valid: Vec<Event> = self
+            .events
+            .iter()
+            .filter(|e| e.timestamp.is_finite())
+            .cloned()
+            .collect();
+
+        valid.sort_by(|a, b| a.timestamp.partial_cmp(&b.timestamp).unwrap());
+
+        valid
+    }
+
+    /// Shared sliding-window core used by every pairwise correlation entry
+    /// point. Events are sorted once by timestamp, then a `left`/`right`
+    /// two-pointer window keeps only the events within `max_time_delta_ms`
+    /// of the current `right` cursor, so only pairs that could possibly
+    /// pass the time-window check are ever compared - no full O(n^2) scan.
+    fn correlate_windowed(
+        &self,
+        should_pair: impl Fn(&str, &str) -> bool,
+        correlation_type: impl Fn(&str, &str) -> String,
+    ) -> Vec<Correlation> {
+        let mut correlations = Vec::new();
+        let sorted_events = self.sorted_valid_events();
+        let mut left = 0usize;
+
+        for right in 0..sorted_events.len() {
+            let target = &sorted_events[right];
+
+            // Drop events from the left of the window once they fall
+            // further than max_time_delta_ms behind the right cursor.
+            while target.timestamp - sorted_events[left].timestamp > self.max_time_delta_ms {
+                left += 1;
+            }
+
+            for source in &sorted_events[left..right] {
+                if !should_pair(&source.event_type, &target.event_type) {
                     continue;
                 }
 
+                let time_delta = target.timestamp - source.timestamp;
                 let confidence = self.calculate_confidence(source, target, time_delta);
 
                 if confidence >= self.min_confidence {
-                    let correlation_type = format!("{}_{}", 
-                        self.normalize_event_type(&source.event_type),
-                        self.normalize_event_type(&target.event_type)
-                    );
-
                     correlations.push(Correlation {
                         source_event_id: source.event_id.clone(),
                         target_event_id: target.event_id.clone(),
                         confidence,
                         time_delta_ms: time_delta,
-                        correlation_type,
+                        correlation_type: correlation_type(&source.event_type, &target.event_type),
+                        matched_key: None,
                     });
                 }
             }
@@ -379,6 +886,32 @@ impl RustCorrelator {
         confidence.min(1.0)
     }
 
+    /// Calculate confidence score using a rule's own time window and weight
+    /// overrides instead of the correlator's global defaults. Type
+    /// compatibility is implied by the caller having already matched a rule.
+    fn calculate_confidence_weighted(
+        &self,
+        source: &Event,
+        target: &Event,
+        time_delta: f64,
+        max_time_delta_ms: f64,
+        time_weight: f64,
+        type_weight: f64,
+        data_weight: f64,
+    ) -> f64 {
+        let mut confidence = 0.0;
+
+        let time_score = 1.0 - (time_delta / max_time_delta_ms);
+        confidence += time_score * time_weight;
+
+        confidence += type_weight;
+
+        let data_score = self.calculate_data_similarity(source, target);
+        confidence += data_score * data_weight;
+
+        confidence.min(1.0)
+    }
+
     /// Check if two event types should be correlated
     fn should_correlate(&self, source_type: &str, target_type: &str) -> bool {
         matches!(
@@ -410,6 +943,10 @@ impl RustCorrelator {
             return 0.0;
         }
 
+        if self.correlation_method != CorrelationMethod::Exact {
+            return self.calculate_numeric_similarity(source, target, &common_keys);
+        }
+
         // Count matching values
         let matches = common_keys.iter()
             .filter(|k| source.data.get(*k) == target.data.get(*k))
@@ -418,12 +955,92 @@ impl RustCorrelator {
         matches as f64 / common_keys.len() as f64
     }
 
+    /// Treat shared `data` keys as aligned numeric samples and correlate them
+    fn calculate_numeric_similarity(
+        &self,
+        source: &Event,
+        target: &Event,
+        common_keys: &[&String],
+    ) -> f64 {
+        let mut xs = Vec::with_capacity(common_keys.len());
+        let mut ys = Vec::with_capacity(common_keys.len());
+
+        for key in common_keys {
+            let x = f64::from_str(source.data.get(*key).unwrap());
+            let y = f64::from_str(target.data.get(*key).unwrap());
+            if let (Ok(x), Ok(y)) = (x, y) {
+                xs.push(x);
+                ys.push(y);
+            }
+        }
+
+        if xs.len() < 3 {
+            return 0.0;
+        }
+
+        let r = match self.correlation_method {
+            CorrelationMethod::Pearson => pearson_correlation(&xs, &ys),
+            CorrelationMethod::Spearman => spearman_correlation(&xs, &ys),
+            CorrelationMethod::Exact => unreachable!("exact method never reaches the numeric path"),
+        };
+
+        r.abs()
+    }
+
     /// Normalize event type for correlation naming
     fn normalize_event_type(&self, event_type: &str) -> String {
         event_type.to_uppercase().replace(' ', "_")
     }
 }
 
+/// Pearson product-moment correlation coefficient over two aligned vectors
+fn pearson_correlation(x: &[f64], y: &[f64]) -> f64 {
+    let n = x.len() as f64;
+
+    let sum_x: f64 = x.iter().sum();
+    let sum_y: f64 = y.iter().sum();
+    let sum_xy: f64 = x.iter().zip(y.iter()).map(|(a, b)| a * b).sum();
+    let sum_x2: f64 = x.iter().map(|a| a * a).sum();
+    let sum_y2: f64 = y.iter().map(|b| b * b).sum();
+
+    let numerator = n * sum_xy - sum_x * sum_y;
+    let denominator = ((n * sum_x2 - sum_x * sum_x) * (n * sum_y2 - sum_y * sum_y)).sqrt();
+
+    if denominator == 0.0 {
+        return 0.0;
+    }
+
+    numerator / denominator
+}
+
+/// Spearman rank correlation: Pearson's formula applied to fractional ranks
+fn spearman_correlation(x: &[f64], y: &[f64]) -> f64 {
+    pearson_correlation(&fractional_ranks(x), &fractional_ranks(y))
+}
+
+/// Convert values to fractional ranks, averaging ranks across tied values
+fn fractional_ranks(values: &[f64]) -> Vec<f64> {
+    let mut indices: Vec<usize> = (0..values.len()).collect();
+    indices.sort_by(|&a, &b| values[a].partial_cmp(&values[b]).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut ranks = vec![0.0; values.len()];
+    let mut i = 0;
+    while i < indices.len() {
+        let mut j = i;
+        while j + 1 < indices.len() && values[indices[j + 1]] == values[indices[i]] {
+            j += 1;
+        }
+        // Average rank (1-based) across the tied run [i, j]
+        let avg_rank = ((i + 1) + (j + 1)) as f64 / 2.0;
+        for idx in indices.iter().take(j + 1).skip(i) {
+            ranks[*idx] = avg_rank;
+        }
+        i = j + 1;
+    }
+
+    ranks
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -440,9 +1057,14 @@ mod tests {
         assert_eq!(event.timestamp, 1000.0);
     }
 
+    #[test]
+    fn test_negative_max_time_delta_ms_is_rejected() {
+        assert!(RustCorrelator::new(-5.0, 0.5).is_err());
+    }
+
     #[test]
     fn test_correlator_basic() {
-        let mut correlator = RustCorrelator::new(5000.0, 0.5);
+        let mut correlator = RustCorrelator::new(5000.0, 0.5).unwrap();
         
         let ui_event = Event::new("ui_1".to_string(), "UI_INTERACTION".to_string(), 1000.0);
         let api_event = Event::new("api_1".to_string(), "API_CALL".to_string(), 1050.0);
@@ -455,7 +1077,7 @@ mod tests {
 
     #[test]
     fn test_correlation_calculation() {
-        let correlator = RustCorrelator::new(5000.0, 0.5);
+        let correlator = RustCorrelator::new(5000.0, 0.5).unwrap();
         
         let source = Event::new("src".to_string(), "UI_INTERACTION".to_string(), 1000.0);
         let target = Event::new("tgt".to_string(), "API_CALL".to_string(), 1100.0);
@@ -467,7 +1089,7 @@ mod tests {
 
     #[test]
     fn test_should_correlate() {
-        let correlator = RustCorrelator::new(5000.0, 0.5);
+        let correlator = RustCorrelator::new(5000.0, 0.5).unwrap();
         
         assert!(correlator.should_correlate("UI_INTERACTION", "API_CALL"));
         assert!(correlator.should_correlate("API_RESPONSE", "NAVIGATION"));
@@ -477,7 +1099,7 @@ mod tests {
     #[test]
     fn test_nan_timestamp_handling() {
         // Bug fix test: NaN timestamps should not cause panic
-        let mut correlator = RustCorrelator::new(5000.0, 0.5);
+        let mut correlator = RustCorrelator::new(5000.0, 0.5).unwrap();
         
         // Add events with valid timestamps
         let event1 = Event::new("e1".to_string(), "UI_INTERACTION".to_string(), 1000.0);
@@ -489,24 +1111,16 @@ mod tests {
         correlator.add_event(event1);
         correlator.add_event(event2);
         correlator.add_event(event_nan);
-        
-        // These operations should not panic even with NaN timestamp
-        let correlations = correlator.find_correlations();
-        assert!(correlations.is_ok(), "find_correlations should not panic with NaN timestamps");
-        
-        let graph = correlator.build_correlation_graph();
-        assert!(graph.is_ok(), "build_correlation_graph should not panic with NaN timestamps");
-        
-        let stats = correlator.get_statistics();
-        assert!(stats.is_ok(), "get_statistics should not panic with NaN timestamps");
-        
+
+        // This should not panic even with a NaN timestamp in the mix.
+        let correlations = correlator.correlate_events();
+
         // Valid events should still correlate properly
-        let correlations = correlations.unwrap();
         let valid_correlations: Vec<_> = correlations
             .iter()
-            .filter(|c| c.source_id != "e_nan" && c.target_id != "e_nan")
+            .filter(|c| c.source_event_id != "e_nan" && c.target_event_id != "e_nan")
             .collect();
-        
+
         // Should have at least one valid correlation between e1 and e2
         assert!(valid_correlations.len() > 0, "Valid events should still correlate");
     }
@@ -514,7 +1128,7 @@ mod tests {
     #[test]
     fn test_infinity_timestamp_handling() {
         // Additional edge case: infinity timestamps
-        let mut correlator = RustCorrelator::new(5000.0, 0.5);
+        let mut correlator = RustCorrelator::new(5000.0, 0.5).unwrap();
         
         let event1 = Event::new("e1".to_string(), "UI_INTERACTION".to_string(), 1000.0);
         let event_inf = Event::new("e_inf".to_string(), "API_CALL".to_string(), f64::INFINITY);
@@ -523,9 +1137,320 @@ mod tests {
         correlator.add_event(event1);
         correlator.add_event(event_inf);
         correlator.add_event(event_neg_inf);
-        
+
         // Should not panic
-        let result = correlator.find_correlations();
-        assert!(result.is_ok(), "Should handle infinity timestamps without panic");
+        let _correlations = correlator.correlate_events();
+    }
+
+    #[test]
+    fn test_pearson_correlation_perfect_linear() {
+        let x = vec![1.0, 2.0, 3.0, 4.0];
+        let y = vec![2.0, 4.0, 6.0, 8.0];
+        assert!((pearson_correlation(&x, &y) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_pearson_correlation_zero_denominator() {
+        let x = vec![5.0, 5.0, 5.0];
+        let y = vec![1.0, 2.0, 3.0];
+        assert_eq!(pearson_correlation(&x, &y), 0.0);
+    }
+
+    #[test]
+    fn test_spearman_correlation_with_ties() {
+        let x = vec![1.0, 2.0, 2.0, 3.0];
+        let y = vec![1.0, 2.0, 2.0, 3.0];
+        assert!((spearman_correlation(&x, &y) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_numeric_similarity_requires_min_pairs() {
+        let mut correlator = RustCorrelator::new(5000.0, 0.5).unwrap();
+        correlator.set_correlation_method(CorrelationMethod::Pearson);
+
+        let mut source = Event::new("src".to_string(), "UI_INTERACTION".to_string(), 1000.0);
+        let mut target = Event::new("tgt".to_string(), "API_CALL".to_string(), 1100.0);
+        source.add_data("offset".to_string(), "10".to_string());
+        target.add_data("offset".to_string(), "20".to_string());
+
+        // Only one aligned numeric pair - below the minimum of 3
+        assert_eq!(correlator.calculate_data_similarity(&source, &target), 0.0);
+    }
+
+    #[test]
+    fn test_numeric_similarity_pearson_end_to_end() {
+        let mut correlator = RustCorrelator::new(5000.0, 0.5).unwrap();
+        correlator.set_correlation_method(CorrelationMethod::Pearson);
+
+        let mut source = Event::new("src".to_string(), "UI_INTERACTION".to_string(), 1000.0);
+        let mut target = Event::new("tgt".to_string(), "API_CALL".to_string(), 1100.0);
+        for (key, (x, y)) in [("a", (1.0, 2.0)), ("b", (2.0, 4.0)), ("c", (3.0, 6.0))] {
+            source.add_data(key.to_string(), x.to_string());
+            target.add_data(key.to_string(), y.to_string());
+        }
+
+        let similarity = correlator.calculate_data_similarity(&source, &target);
+        assert!((similarity - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_sweep_line_scales_past_quadratic() {
+        // 50k events spread over a long timeline with a narrow correlation
+        // window: a naive O(n^2) scan would compare ~1.25 billion pairs,
+        // while the windowed sweep only ever looks at events within
+        // max_time_delta_ms of each other.
+        let mut correlator = RustCorrelator::new(50.0, 0.0).unwrap();
+        for i in 0..50_000 {
+            let event_type = if i % 2 == 0 { "UI_INTERACTION" } else { "API_CALL" };
+            correlator.add_event(Event::new(format!("e{}", i), event_type.to_string(), i as f64));
+        }
+
+        let start = std::time::Instant::now();
+        let correlations = correlator.correlate_events();
+        let elapsed = start.elapsed();
+
+        assert!(!correlations.is_empty());
+        assert!(
+            elapsed.as_secs() < 5,
+            "windowed correlation over 50k events took too long: {:?}",
+            elapsed
+        );
+    }
+
+    #[test]
+    fn test_windowed_core_matches_original_pairwise_semantics() {
+        let mut correlator = RustCorrelator::new(500.0, 0.5).unwrap();
+        let ui = Event::new("ui".to_string(), "UI_INTERACTION".to_string(), 1000.0);
+        let api_in_window = Event::new("api_in".to_string(), "API_CALL".to_string(), 1200.0);
+        let api_outside_window = Event::new("api_out".to_string(), "API_CALL".to_string(), 2000.0);
+
+        correlator.add_event(ui);
+        correlator.add_event(api_in_window);
+        correlator.add_event(api_outside_window);
+
+        let correlations = correlator.correlate_windowed(
+            |s, t| s == "UI_INTERACTION" && t == "API_CALL",
+            |_, _| "UI_TO_API".to_string(),
+        );
+
+        assert_eq!(correlations.len(), 1);
+        assert_eq!(correlations[0].target_event_id, "api_in");
+    }
+
+    #[test]
+    fn test_rule_registry_overrides_builtin_pairs() {
+        let mut correlator = RustCorrelator::new(5000.0, 0.5).unwrap();
+
+        // SCREEN_CHANGE -> API_CALL isn't in the built-in should_correlate
+        // list, but a registered rule should allow it through.
+        correlator.add_rule(CorrelationRule::new(
+            "SCREEN_CHANGE".to_string(),
+            "API_CALL".to_string(),
+            2000.0,
+            0.1,
+            0.4,
+            0.3,
+            0.3,
+        ));
+
+        let screen = Event::new("s1".to_string(), "SCREEN_CHANGE".to_string(), 1000.0);
+        let api = Event::new("a1".to_string(), "API_CALL".to_string(), 1500.0);
+        correlator.add_event(screen);
+        correlator.add_event(api);
+
+        let correlations = correlator.correlate_events();
+        assert_eq!(correlations.len(), 1);
+        assert_eq!(correlations[0].source_event_id, "s1");
+        assert_eq!(correlations[0].target_event_id, "a1");
+    }
+
+    #[test]
+    fn test_clear_rules_restores_builtin_behavior() {
+        let mut correlator = RustCorrelator::new(5000.0, 0.5).unwrap();
+        correlator.add_rule(CorrelationRule::new(
+            "SCREEN_CHANGE".to_string(),
+            "API_CALL".to_string(),
+            2000.0,
+            0.1,
+            0.4,
+            0.3,
+            0.3,
+        ));
+        correlator.clear_rules();
+
+        let screen = Event::new("s1".to_string(), "SCREEN_CHANGE".to_string(), 1000.0);
+        let api = Event::new("a1".to_string(), "API_CALL".to_string(), 1500.0);
+        correlator.add_event(screen);
+        correlator.add_event(api);
+
+        // Without rules, SCREEN_CHANGE -> API_CALL isn't a built-in pair.
+        assert!(correlator.correlate_events().is_empty());
+    }
+
+    #[test]
+    fn test_causal_chain_reconstruction() {
+        let mut correlator = RustCorrelator::new(5000.0, 0.1).unwrap();
+
+        correlator.add_event(Event::new("ui".to_string(), "UI_INTERACTION".to_string(), 1000.0));
+        correlator.add_event(Event::new("call".to_string(), "API_CALL".to_string(), 1100.0));
+        correlator.add_event(Event::new("resp".to_string(), "API_RESPONSE".to_string(), 1200.0));
+        correlator.add_event(Event::new("nav".to_string(), "NAVIGATION".to_string(), 1300.0));
+
+        let chains = correlator.causal_chains();
+        assert!(!chains.is_empty());
+
+        let full_chain = chains
+            .iter()
+            .find(|c| c.event_ids == vec!["ui", "call", "resp", "nav"]);
+        assert!(full_chain.is_some(), "expected a full 4-hop chain, got {:?}", chains);
+
+        let chain = full_chain.unwrap();
+        assert!((chain.total_time_ms - 300.0).abs() < 1e-9);
+        assert!(chain.confidence > 0.0 && chain.confidence <= 1.0);
+    }
+
+    #[test]
+    fn test_causal_chain_breaks_cycles() {
+        let mut correlator = RustCorrelator::new(5000.0, 0.1).unwrap();
+        correlator.add_rule(CorrelationRule::new(
+            "API_CALL".to_string(),
+            "API_RESPONSE".to_string(),
+            5000.0,
+            0.1,
+            0.4,
+            0.3,
+            0.3,
+        ));
+        correlator.add_rule(CorrelationRule::new(
+            "API_RESPONSE".to_string(),
+            "API_CALL".to_string(),
+            5000.0,
+            0.1,
+            0.4,
+            0.3,
+            0.3,
+        ));
+
+        for i in 0..6 {
+            let event_type = if i % 2 == 0 { "API_CALL" } else { "API_RESPONSE" };
+            correlator.add_event(Event::new(format!("e{}", i), event_type.to_string(), i as f64 * 100.0));
+        }
+
+        // The API_CALL <-> API_RESPONSE rule pair must still yield chains.
+        let chains = correlator.causal_chains();
+        assert!(!chains.is_empty());
+
+        // Correlations only point forward in time, so the events above form
+        // an acyclic graph; exercise the on-path guard on a real a<->b cycle.
+        let mut graph: HashMap<String, Vec<(String, f64)>> = HashMap::new();
+        graph.insert("a".to_string(), vec![("b".to_string(), 0.5)]);
+        graph.insert("b".to_string(), vec![("a".to_string(), 0.5)]);
+        let mut on_path = std::collections::HashSet::new();
+        let mut out = Vec::new();
+        correlator.dfs_causal_chains("a", &graph, &mut on_path, vec!["a".to_string()], 1.0, &mut out);
+        assert_eq!(out, vec![(vec!["a".to_string(), "b".to_string()], 0.5)]);
+    }
+
+    #[test]
+    fn test_causal_chains_cap_combinatorial_blowup_on_dense_graph() {
+        let mut correlator = RustCorrelator::new(100_000.0, 0.0).unwrap();
+        // Every event correlates with every later one (same type, wide
+        // window), so without a cap this graph's simple-path count grows
+        // like 2^(n-1). With the cap, this must still finish promptly.
+        for i in 0..20 {
+            correlator.add_event(Event::new(
+                format!("e{}", i),
+                "UI_INTERACTION".to_string(),
+                i as f64 * 10.0,
+            ));
+        }
+        correlator.add_rule(CorrelationRule::new(
+            "UI_INTERACTION".to_string(),
+            "UI_INTERACTION".to_string(),
+            100_000.0,
+            0.0,
+            0.0,
+            1.0,
+            0.0,
+        ));
+
+        let chains = correlator.causal_chains();
+        assert!(!chains.is_empty());
+        assert!(chains.len() <= MAX_CAUSAL_CHAINS);
+    }
+
+    #[test]
+    fn test_join_key_short_circuits_confidence() {
+        let mut correlator = RustCorrelator::new(5000.0, 0.9).unwrap();
+        correlator.set_join_keys(vec!["request_id".to_string()]);
+
+        let mut tap = Event::new("tap".to_string(), "UI_INTERACTION".to_string(), 1000.0);
+        let mut call = Event::new("call".to_string(), "API_CALL".to_string(), 60_000.0);
+        tap.add_data("request_id".to_string(), "req-42".to_string());
+        call.add_data("request_id".to_string(), "req-42".to_string());
+
+        correlator.add_event(tap);
+        correlator.add_event(call);
+
+        // Far outside the time window, so the heuristic path alone would
+        // never have found this - the join key must short-circuit it.
+        let correlations = correlator.correlate_events();
+        assert_eq!(correlations.len(), 1);
+        assert_eq!(correlations[0].confidence, 1.0);
+        assert_eq!(correlations[0].matched_key.as_deref(), Some("request_id"));
+        assert_eq!(correlations[0].source_event_id, "tap");
+        assert_eq!(correlations[0].target_event_id, "call");
+    }
+
+    #[test]
+    fn test_join_key_falls_back_to_heuristic_without_shared_key() {
+        let mut correlator = RustCorrelator::new(5000.0, 0.5).unwrap();
+        correlator.set_join_keys(vec!["request_id".to_string()]);
+
+        let ui_event = Event::new("ui_1".to_string(), "UI_INTERACTION".to_string(), 1000.0);
+        let api_event = Event::new("api_1".to_string(), "API_CALL".to_string(), 1050.0);
+        correlator.add_event(ui_event);
+        correlator.add_event(api_event);
+
+        let correlations = correlator.correlate_events();
+        assert_eq!(correlations.len(), 1);
+        assert!(correlations[0].matched_key.is_none());
+    }
+
+    #[test]
+    fn test_segment_sessions_splits_on_idle_gap() {
+        let mut correlator = RustCorrelator::new(5000.0, 0.5).unwrap();
+        correlator.add_event(Event::new("e1".to_string(), "UI_INTERACTION".to_string(), 0.0));
+        correlator.add_event(Event::new("e2".to_string(), "API_CALL".to_string(), 500.0));
+        // Big idle gap here
+        correlator.add_event(Event::new("e3".to_string(), "UI_INTERACTION".to_string(), 20_000.0));
+        correlator.add_event(Event::new("e4".to_string(), "API_CALL".to_string(), 20_300.0));
+
+        let sessions = correlator.compute_sessions(5000.0);
+        assert_eq!(sessions.len(), 2);
+
+        assert_eq!(sessions[0].event_count, 2);
+        assert_eq!(sessions[0].start_time, 0.0);
+        assert_eq!(sessions[0].end_time, 500.0);
+
+        assert_eq!(sessions[1].event_count, 2);
+        assert_eq!(sessions[1].start_time, 20_000.0);
+        assert_eq!(sessions[1].end_time, 20_300.0);
+
+        // Each session's correlations should stay within its own bounds
+        assert!(sessions[0].correlations.iter().all(|c| c.source_event_id == "e1"));
+        assert!(sessions[1].correlations.iter().all(|c| c.source_event_id == "e3"));
+    }
+
+    #[test]
+    fn test_segment_sessions_excludes_nan_and_infinite_events() {
+        let mut correlator = RustCorrelator::new(5000.0, 0.5).unwrap();
+        correlator.add_event(Event::new("e1".to_string(), "UI_INTERACTION".to_string(), 0.0));
+        correlator.add_event(Event::new("e_nan".to_string(), "UI_INTERACTION".to_string(), f64::NAN));
+        correlator.add_event(Event::new("e_inf".to_string(), "API_CALL".to_string(), f64::INFINITY));
+
+        let sessions = correlator.compute_sessions(5000.0);
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].event_count, 1);
     }
 }
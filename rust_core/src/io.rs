@@ -3,72 +3,507 @@
 //! High-performance file operations using Rust's async I/O
 
 use pyo3::prelude::*;
-use pyo3::types::{PyDict, PyList};
+use pyo3::types::{PyBytes, PyDict, PyList, PyTuple};
 use std::fs;
-use std::path::{Path, PathBuf};
-use std::io::{self, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::io::{self, Read, Seek, SeekFrom, Write};
 use rayon::prelude::*;
 use walkdir::WalkDir;
+use regex::Regex;
 
-/// Read a file quickly
+pyo3::create_exception!(io, PathEscapesRootError, pyo3::exceptions::PyException);
+
+/// Lexically resolve `.` and `..` components without touching the
+/// filesystem (used for the portion of a path that doesn't exist yet).
+fn lexically_normalize(path: &Path) -> PathBuf {
+    let mut result = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                result.pop();
+            }
+            Component::CurDir => {}
+            other => result.push(other.as_os_str()),
+        }
+    }
+    result
+}
+
+/// Canonicalize `path`, following symlinks and resolving `.`/`..`, even
+/// when `path` (or part of it) doesn't exist yet: the longest existing
+/// ancestor is resolved with `fs::canonicalize`, and the remaining,
+/// not-yet-created tail is appended and normalized lexically.
+fn canonicalize_lenient(path: &Path) -> PyResult<PathBuf> {
+    let mut existing = path;
+    let mut tail: Vec<&std::ffi::OsStr> = Vec::new();
+
+    loop {
+        match fs::canonicalize(existing) {
+            Ok(mut base) => {
+                for component in tail.iter().rev() {
+                    base.push(component);
+                }
+                return Ok(lexically_normalize(&base));
+            }
+            Err(e) => match existing.parent() {
+                Some(parent) => {
+                    // The last component, `..` included (`file_name()` would
+                    // drop it), so the lexical pass below can resolve it.
+                    if let Some(last) = existing.components().next_back() {
+                        tail.push(last.as_os_str());
+                    }
+                    existing = parent;
+                }
+                None => {
+                    return Err(PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
+                        "Failed to resolve path '{}': {}",
+                        path.display(),
+                        e
+                    )));
+                }
+            },
+        }
+    }
+}
+
+/// Resolve `path` (joined onto `root` if relative) to its canonical form
+/// and verify it is still a descendant of `root`, borrowing Mercurial's
+/// `canonical_path` guard against `../` and symlink escapes from
+/// untrusted manifests. Returns `PathEscapesRootError` if it is not.
+pub fn canonicalize_within(root: &str, path: &str) -> PyResult<PathBuf> {
+    let root_canonical = fs::canonicalize(root).map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to resolve root '{}': {}", root, e))
+    })?;
+
+    let candidate = Path::new(path);
+    let joined = if candidate.is_absolute() {
+        candidate.to_path_buf()
+    } else {
+        root_canonical.join(candidate)
+    };
+
+    let resolved = canonicalize_lenient(&joined)?;
+
+    if resolved.starts_with(&root_canonical) {
+        Ok(resolved)
+    } else {
+        Err(PathEscapesRootError::new_err(format!(
+            "path '{}' escapes root '{}'",
+            path, root
+        )))
+    }
+}
+
+/// Resolve `path` against an optional sandbox `root`, passing it through
+/// unchanged when no root is given.
+fn resolve_path(path: &str, root: Option<&str>) -> PyResult<PathBuf> {
+    match root {
+        Some(root) => canonicalize_within(root, path),
+        None => Ok(PathBuf::from(path)),
+    }
+}
+
+/// Coarse classification of a filesystem entry, in the spirit of
+/// Mercurial's "bad file type" handling - lets callers recognize FIFOs,
+/// sockets, device nodes, and broken symlinks instead of hanging or
+/// erroring obscurely when something tries to `read_to_string` them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[pyclass]
+pub enum FileKind {
+    Regular,
+    Directory,
+    Symlink,
+    BrokenSymlink,
+    Fifo,
+    Socket,
+    CharacterDevice,
+    BlockDevice,
+    Unknown,
+}
+
+impl FileKind {
+    /// Human-readable reason used when this kind causes a path to be skipped
+    fn skip_reason(self) -> &'static str {
+        match self {
+            FileKind::Directory => "directory",
+            FileKind::BrokenSymlink => "broken symlink",
+            FileKind::Fifo => "fifo",
+            FileKind::Socket => "socket",
+            FileKind::CharacterDevice => "character device",
+            FileKind::BlockDevice => "block device",
+            FileKind::Regular | FileKind::Symlink | FileKind::Unknown => "unreadable",
+        }
+    }
+
+    /// Whether this kind should be skipped rather than read as text
+    fn is_special(self) -> bool {
+        matches!(
+            self,
+            FileKind::Directory
+                | FileKind::BrokenSymlink
+                | FileKind::Fifo
+                | FileKind::Socket
+                | FileKind::CharacterDevice
+                | FileKind::BlockDevice
+        )
+    }
+}
+
+/// Classify a path without following symlinks first, matching broken
+/// symlinks and non-regular Unix file types. Falls back to a plain
+/// regular/directory/unknown classification on non-Unix platforms.
+fn classify_path(path: &Path) -> FileKind {
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(_) => return FileKind::Unknown,
+    };
+    let file_type = metadata.file_type();
+
+    if file_type.is_symlink() {
+        return if fs::metadata(path).is_ok() {
+            FileKind::Symlink
+        } else {
+            FileKind::BrokenSymlink
+        };
+    }
+    if file_type.is_dir() {
+        return FileKind::Directory;
+    }
+    if file_type.is_file() {
+        return FileKind::Regular;
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+        if file_type.is_fifo() {
+            return FileKind::Fifo;
+        }
+        if file_type.is_socket() {
+            return FileKind::Socket;
+        }
+        if file_type.is_char_device() {
+            return FileKind::CharacterDevice;
+        }
+        if file_type.is_block_device() {
+            return FileKind::BlockDevice;
+        }
+    }
+
+    FileKind::Unknown
+}
+
+/// Read a file quickly, optionally sandboxed to `root` (see `canonicalize_within`)
 #[pyfunction]
-pub fn read_file_fast(path: &str) -> PyResult<String> {
-    fs::read_to_string(path).map_err(|e| {
+#[pyo3(signature = (path, root = None))]
+pub fn read_file_fast(path: &str, root: Option<&str>) -> PyResult<String> {
+    let resolved = resolve_path(path, root)?;
+    fs::read_to_string(&resolved).map_err(|e| {
         PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to read file: {}", e))
     })
 }
 
-/// Write a file quickly
+/// Write a file quickly, optionally sandboxed to `root` (see `canonicalize_within`)
 #[pyfunction]
-pub fn write_file_fast(path: &str, content: &str) -> PyResult<()> {
-    fs::write(path, content).map_err(|e| {
+#[pyo3(signature = (path, content, root = None))]
+pub fn write_file_fast(path: &str, content: &str, root: Option<&str>) -> PyResult<()> {
+    let resolved = resolve_path(path, root)?;
+    fs::write(&resolved, content).map_err(|e| {
         PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to write file: {}", e))
     })
 }
 
-/// Read multiple files in parallel
+/// Read multiple files in parallel. Returns `(contents, skipped)`: `contents`
+/// maps path to text (an empty file maps to an empty string, and a missing
+/// or otherwise unreadable file to `None`), `skipped` maps path to a reason for paths that were a
+/// special file type (directory, FIFO, socket, device node, broken symlink)
+/// rather than something `read_to_string` could ever succeed on.
 #[pyfunction]
-pub fn read_files_parallel(py: Python, paths: Vec<String>) -> PyResult<PyObject> {
-    let results: Vec<(String, Result<String, io::Error>)> = paths
+pub fn read_files_parallel(py: Python, paths: Vec<String>) -> PyResult<(PyObject, PyObject)> {
+    let results: Vec<(String, Option<FileKind>, Result<String, io::Error>)> = paths
         .par_iter()
         .map(|path| {
-            let content = fs::read_to_string(path);
-            (path.clone(), content)
+            let kind = classify_path(Path::new(path));
+            if kind.is_special() {
+                let err = io::Error::new(io::ErrorKind::Other, kind.skip_reason());
+                (path.clone(), Some(kind), Err(err))
+            } else {
+                (path.clone(), None, fs::read_to_string(path))
+            }
         })
         .collect();
 
-    // Convert to Python dict
-    let py_dict = PyDict::new(py);
-    for (path, result) in results {
-        match result {
-            Ok(content) => {
-                py_dict.set_item(path, content)?;
+    let contents = PyDict::new(py);
+    let skipped = PyDict::new(py);
+
+    for (path, kind, result) in results {
+        match (kind, result) {
+            (Some(kind), Err(_)) => {
+                skipped.set_item(path, kind.skip_reason())?;
+            }
+            (_, Ok(content)) => {
+                contents.set_item(path, content)?;
+            }
+            (None, Err(_)) => {
+                contents.set_item(path, py.None())?;
+            }
+        }
+    }
+
+    Ok((contents.into(), skipped.into()))
+}
+
+/// Split a Mercurial-style file pattern into its kind and the remainder
+/// after the `kind:` prefix, defaulting to `glob` when no known prefix is present.
+fn split_pattern_kind(pattern: &str) -> (&str, &str) {
+    for kind in ["glob", "re", "path", "rootfilesin"] {
+        if let Some(rest) = pattern.strip_prefix(&format!("{}:", kind)) {
+            return (kind, rest);
+        }
+    }
+    ("glob", pattern)
+}
+
+/// Translate a shell glob into an (unanchored) regex body: `*` matches any
+/// run of characters except `/`, `**` matches across `/` (and `**/` also
+/// matches no directory at all), `?` matches one character other than `/`,
+/// and `[...]` classes pass through to the underlying regex engine.
+fn glob_to_regex_body(glob: &str) -> String {
+    let chars: Vec<char> = glob.chars().collect();
+    let mut regex = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '*' => {
+                if chars.get(i + 1) == Some(&'*') && chars.get(i + 2) == Some(&'/') {
+                    regex.push_str("(?:.*/)?");
+                    i += 3;
+                } else if chars.get(i + 1) == Some(&'*') {
+                    regex.push_str(".*");
+                    i += 2;
+                } else {
+                    regex.push_str("[^/]*");
+                    i += 1;
+                }
             }
-            Err(_) => {
-                py_dict.set_item(path, py.None())?;
+            '?' => {
+                regex.push_str("[^/]");
+                i += 1;
+            }
+            '[' => {
+                let start = i;
+                i += 1;
+                if chars.get(i) == Some(&'!') || chars.get(i) == Some(&'^') {
+                    i += 1;
+                }
+                while i < chars.len() && chars[i] != ']' {
+                    i += 1;
+                }
+                if i < chars.len() {
+                    i += 1; // consume the closing ']'
+                }
+                let class: String = chars[start..i].iter().collect();
+                // Only a *leading* `!` negates the class (glob's `[!...]`),
+                // same as `^` does for a regex character class. `!` anywhere
+                // else in the body (e.g. `[abc!]`) is a literal member and
+                // must pass through unchanged.
+                let class = match class.strip_prefix("[!") {
+                    Some(rest) => format!("[^{}", rest),
+                    None => class,
+                };
+                regex.push_str(&class);
+            }
+            c if "\\.+()|^${}".contains(c) => {
+                regex.push('\\');
+                regex.push(c);
+                i += 1;
+            }
+            c => {
+                regex.push(c);
+                i += 1;
             }
         }
     }
 
-    Ok(py_dict.into())
+    regex
+}
+
+/// Translate a shell glob into an anchored regex matching a full path
+fn glob_to_regex(glob: &str) -> String {
+    format!("^{}$", glob_to_regex_body(glob))
 }
 
-/// Find files matching a pattern
+/// Compile one Mercurial-style file pattern into a `Regex` matched against a
+/// repo-relative path (always normalized to `/` separators).
+fn compile_pattern(pattern: &str) -> Result<Regex, String> {
+    let (kind, rest) = split_pattern_kind(pattern);
+
+    let regex_source = match kind {
+        "glob" => glob_to_regex(rest),
+        "re" => rest.to_string(),
+        "path" => format!("^{}$", regex::escape(rest)),
+        "rootfilesin" => {
+            let dir = rest.trim_end_matches('/');
+            if dir.is_empty() {
+                "^[^/]+$".to_string()
+            } else {
+                format!("^{}/[^/]+$", regex::escape(dir))
+            }
+        }
+        _ => unreachable!("split_pattern_kind only returns known kinds"),
+    };
+
+    Regex::new(&regex_source).map_err(|e| format!("invalid pattern '{}': {}", pattern, e))
+}
+
+/// Path of `path` relative to `root`, normalized to `/` separators
+fn relative_path_str(root: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    relative.to_string_lossy().replace('\\', "/")
+}
+
+/// A compiled `.hgignore`/`.gitignore`-style matcher. Rules are applied in
+/// order and the last matching rule wins, so a later `!pattern` line can
+/// re-include something excluded earlier.
+struct IgnoreMatcher {
+    rules: Vec<(Regex, bool)>, // (pattern, is_negation)
+}
+
+impl IgnoreMatcher {
+    /// Whether `relative_path` (a file or directory, `/`-separated) is ignored
+    fn is_ignored(&self, relative_path: &str) -> bool {
+        let mut ignored = false;
+        for (pattern, is_negation) in &self.rules {
+            if pattern.is_match(relative_path) {
+                ignored = !is_negation;
+            }
+        }
+        ignored
+    }
+}
+
+/// Parse ignore-file lines (blank lines and `#` comments are skipped, a
+/// leading `!` re-includes a previously excluded path) into a matcher.
+/// A pattern anchored with a leading `/` only matches from the root;
+/// otherwise it matches at any depth, the same as `.gitignore`.
+fn parse_ignore_patterns(lines: &[String]) -> Result<IgnoreMatcher, String> {
+    let mut rules = Vec::new();
+
+    for raw_line in lines {
+        let line = raw_line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (is_negation, pattern) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+
+        let anchored = pattern.starts_with('/');
+        let pattern = pattern.trim_start_matches('/').trim_end_matches('/');
+        let body = glob_to_regex_body(pattern);
+
+        let regex_source = if anchored {
+            format!("^{}(/.*)?$", body)
+        } else {
+            format!("(^|.*/){}(/.*)?$", body)
+        };
+
+        let regex = Regex::new(&regex_source)
+            .map_err(|e| format!("invalid ignore pattern '{}': {}", line, e))?;
+        rules.push((regex, is_negation));
+    }
+
+    Ok(IgnoreMatcher { rules })
+}
+
+/// Reads one or more ignore files (e.g. `.gitignore`/`.hgignore`) from disk,
+/// in order, and returns their concatenated lines, ready to hand to
+/// `parse_ignore_patterns` alongside (or instead of) an inline list.
+fn read_ignore_file_lines(paths: &[String]) -> Result<Vec<String>, String> {
+    let mut lines = Vec::new();
+    for path in paths {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("failed to read ignore file '{}': {}", path, e))?;
+        lines.extend(content.lines().map(|line| line.to_string()));
+    }
+    Ok(lines)
+}
+
+/// Reads one or more ignore files (e.g. `.gitignore`/`.hgignore`) from disk
+/// and parses their combined lines into a matcher, in the same format
+/// `parse_ignore_patterns` expects for an inline list. Files are read in
+/// order and their lines concatenated, so a later file's `!pattern` can
+/// re-include something an earlier file excluded.
+fn load_ignore_files(paths: &[String]) -> Result<IgnoreMatcher, String> {
+    parse_ignore_patterns(&read_ignore_file_lines(paths)?)
+}
+
+/// Combines an inline `ignore_patterns` list with the contents of one or
+/// more on-disk `ignore_files` into a single matcher, `ignore_files`' rules
+/// applied first so inline patterns can override them. Returns `None` when
+/// neither is given.
+fn build_ignore_matcher(
+    ignore_patterns: Option<Vec<String>>,
+    ignore_files: Option<Vec<String>>,
+) -> Result<Option<IgnoreMatcher>, String> {
+    if ignore_patterns.is_none() && ignore_files.is_none() {
+        return Ok(None);
+    }
+
+    let mut lines = match ignore_files {
+        Some(paths) => read_ignore_file_lines(&paths)?,
+        None => Vec::new(),
+    };
+    lines.extend(ignore_patterns.unwrap_or_default());
+
+    parse_ignore_patterns(&lines).map(Some)
+}
+
+/// Find files matching any of the given Mercurial-style patterns, e.g.
+/// `["glob:**/*.kt", "re:.*Test\\.java$"]`, optionally pruning directories
+/// and files matched by `ignore_patterns` (inline `.gitignore`/`.hgignore`-
+/// style lines) and/or `ignore_files` (paths to actual `.gitignore`/
+/// `.hgignore` files on disk, read and combined with `ignore_patterns`).
 #[pyfunction]
-pub fn find_files(py: Python, root_dir: &str, pattern: &str) -> PyResult<PyObject> {
+#[pyo3(signature = (root_dir, patterns, ignore_patterns = None, ignore_files = None))]
+pub fn find_files(
+    py: Python,
+    root_dir: &str,
+    patterns: Vec<String>,
+    ignore_patterns: Option<Vec<String>>,
+    ignore_files: Option<Vec<String>>,
+) -> PyResult<PyObject> {
+    let compiled: Vec<Regex> = patterns
+        .iter()
+        .map(|p| compile_pattern(p))
+        .collect::<Result<Vec<_>, String>>()
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e))?;
+
+    let ignore_matcher = build_ignore_matcher(ignore_patterns, ignore_files)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e))?;
+
+    let root = Path::new(root_dir);
     let mut matching_files = Vec::new();
 
-    for entry in WalkDir::new(root_dir)
-        .follow_links(true)
-        .into_iter()
-        .filter_map(|e| e.ok())
-    {
+    let walker = WalkDir::new(root_dir).follow_links(true).into_iter().filter_entry(|entry| {
+        match &ignore_matcher {
+            // Pruning an ignored *directory* here stops WalkDir from
+            // descending into it at all, instead of filtering its
+            // contents out afterward.
+            Some(matcher) => !matcher.is_ignored(&relative_path_str(root, entry.path())),
+            None => true,
+        }
+    });
+
+    for entry in walker.filter_map(|e| e.ok()) {
         let path = entry.path();
         if path.is_file() {
-            if let Some(file_name) = path.file_name() {
-                if file_name.to_string_lossy().contains(pattern) {
-                    matching_files.push(path.to_string_lossy().to_string());
-                }
+            let relative = relative_path_str(root, path);
+            if compiled.iter().any(|re| re.is_match(&relative)) {
+                matching_files.push(path.to_string_lossy().to_string());
             }
         }
     }
@@ -98,15 +533,29 @@ pub fn file_exists(path: &str) -> bool {
     Path::new(path).exists()
 }
 
-/// Get directory size recursively
+/// Get directory size recursively, optionally pruning paths matched by
+/// `ignore_patterns` (inline `.gitignore`/`.hgignore`-style lines) and/or
+/// `ignore_files` (paths to actual ignore files on disk) before descending
+/// into them.
 #[pyfunction]
-pub fn get_directory_size(path: &str) -> PyResult<u64> {
+#[pyo3(signature = (path, ignore_patterns = None, ignore_files = None))]
+pub fn get_directory_size(
+    path: &str,
+    ignore_patterns: Option<Vec<String>>,
+    ignore_files: Option<Vec<String>>,
+) -> PyResult<u64> {
+    let ignore_matcher = build_ignore_matcher(ignore_patterns, ignore_files)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e))?;
+
+    let root = Path::new(path);
     let mut total_size = 0u64;
 
-    for entry in WalkDir::new(path)
-        .into_iter()
-        .filter_map(|e| e.ok())
-    {
+    let walker = WalkDir::new(path).into_iter().filter_entry(|entry| match &ignore_matcher {
+        Some(matcher) => !matcher.is_ignored(&relative_path_str(root, entry.path())),
+        None => true,
+    });
+
+    for entry in walker.filter_map(|e| e.ok()) {
         if entry.path().is_file() {
             if let Ok(metadata) = entry.metadata() {
                 total_size += metadata.len();
@@ -135,26 +584,56 @@ pub fn list_directory(py: Python, path: &str) -> PyResult<PyObject> {
     Ok(py_list.into())
 }
 
-/// Copy file with progress
+/// List directory contents along with each entry's classified `FileKind`,
+/// so callers can distinguish regular files from directories, symlinks, and
+/// special files without a second syscall per entry.
 #[pyfunction]
-pub fn copy_file_fast(source: &str, destination: &str) -> PyResult<u64> {
-    fs::copy(source, destination).map_err(|e| {
+pub fn list_directory_typed(py: Python, path: &str) -> PyResult<PyObject> {
+    let entries = fs::read_dir(path).map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to list directory: {}", e))
+    })?;
+
+    let py_list = PyList::empty(py);
+
+    for entry in entries.filter_map(|e| e.ok()) {
+        if let Ok(file_name) = entry.file_name().into_string() {
+            let kind = classify_path(&entry.path());
+            let tuple = PyTuple::new(py, &[file_name.into_py(py), Py::new(py, kind)?.into_py(py)]);
+            py_list.append(tuple)?;
+        }
+    }
+
+    Ok(py_list.into())
+}
+
+/// Copy file with progress, optionally sandboxing both endpoints to `root`
+#[pyfunction]
+#[pyo3(signature = (source, destination, root = None))]
+pub fn copy_file_fast(source: &str, destination: &str, root: Option<&str>) -> PyResult<u64> {
+    let resolved_source = resolve_path(source, root)?;
+    let resolved_destination = resolve_path(destination, root)?;
+    fs::copy(&resolved_source, &resolved_destination).map_err(|e| {
         PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to copy file: {}", e))
     })
 }
 
-/// Move/rename file
+/// Move/rename file, optionally sandboxing both endpoints to `root`
 #[pyfunction]
-pub fn move_file(source: &str, destination: &str) -> PyResult<()> {
-    fs::rename(source, destination).map_err(|e| {
+#[pyo3(signature = (source, destination, root = None))]
+pub fn move_file(source: &str, destination: &str, root: Option<&str>) -> PyResult<()> {
+    let resolved_source = resolve_path(source, root)?;
+    let resolved_destination = resolve_path(destination, root)?;
+    fs::rename(&resolved_source, &resolved_destination).map_err(|e| {
         PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to move file: {}", e))
     })
 }
 
-/// Delete file
+/// Delete file, optionally sandboxed to `root` (see `canonicalize_within`)
 #[pyfunction]
-pub fn delete_file(path: &str) -> PyResult<()> {
-    fs::remove_file(path).map_err(|e| {
+#[pyo3(signature = (path, root = None))]
+pub fn delete_file(path: &str, root: Option<&str>) -> PyResult<()> {
+    let resolved = resolve_path(path, root)?;
+    fs::remove_file(&resolved).map_err(|e| {
         PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to delete file: {}", e))
     })
 }
@@ -167,10 +646,12 @@ pub fn create_directory(path: &str) -> PyResult<()> {
     })
 }
 
-/// Delete directory recursively
+/// Delete directory recursively, optionally sandboxed to `root` (see `canonicalize_within`)
 #[pyfunction]
-pub fn delete_directory(path: &str) -> PyResult<()> {
-    fs::remove_dir_all(path).map_err(|e| {
+#[pyo3(signature = (path, root = None))]
+pub fn delete_directory(path: &str, root: Option<&str>) -> PyResult<()> {
+    let resolved = resolve_path(path, root)?;
+    fs::remove_dir_all(&resolved).map_err(|e| {
         PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to delete directory: {}", e))
     })
 }
@@ -195,40 +676,204 @@ pub fn get_file_mtime(path: &str) -> PyResult<f64> {
     Ok(duration.as_secs_f64())
 }
 
-/// Read file in chunks (for large files)
+/// Read exactly `length` bytes starting at `offset`, as raw `bytes` that
+/// are never decoded, for byte-accurate access into large binary capture
+/// files (screenshots, trace blobs). Returns fewer bytes than requested
+/// if the file is shorter than `offset + length`.
 #[pyfunction]
-pub fn read_file_chunked(py: Python, path: &str, chunk_size: usize) -> PyResult<PyObject> {
-    let file = fs::File::open(path).map_err(|e| {
+pub fn read_file_range(py: Python, path: &str, offset: u64, length: usize) -> PyResult<Py<PyBytes>> {
+    let mut file = fs::File::open(path).map_err(|e| {
         PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to open file: {}", e))
     })?;
 
-    let mut reader = io::BufReader::new(file);
-    let mut chunks = Vec::new();
-    let mut buffer = vec![0u8; chunk_size];
+    file.seek(SeekFrom::Start(offset)).map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to seek: {}", e))
+    })?;
 
-    loop {
-        match reader.read(&mut buffer) {
-            Ok(0) => break, // EOF
-            Ok(n) => {
-                let chunk = String::from_utf8_lossy(&buffer[..n]).to_string();
-                chunks.push(chunk);
-            }
+    let mut buffer = vec![0u8; length];
+    let mut read_total = 0usize;
+
+    while read_total < length {
+        match file.read(&mut buffer[read_total..]) {
+            Ok(0) => break, // EOF before `length` bytes were available
+            Ok(n) => read_total += n,
             Err(e) => {
                 return Err(PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
-                    "Failed to read chunk: {}",
+                    "Failed to read range: {}",
                     e
                 )));
             }
         }
     }
 
-    // Convert to Python list
-    let py_list = PyList::empty(py);
-    for chunk in chunks {
-        py_list.append(chunk)?;
+    Ok(Py::from(PyBytes::new(py, &buffer[..read_total])))
+}
+
+/// Lazily yields fixed-size `bytes` chunks from a file without ever
+/// decoding it as text, so binary capture files aren't corrupted and huge
+/// recorder logs can be streamed without materializing them in memory.
+#[pyclass]
+pub struct ChunkedBytesReader {
+    reader: io::BufReader<fs::File>,
+    chunk_size: usize,
+}
+
+impl ChunkedBytesReader {
+    /// Read the next chunk, or `None` at EOF
+    fn next_chunk(&mut self) -> io::Result<Option<Vec<u8>>> {
+        let mut buffer = vec![0u8; self.chunk_size];
+        let n = self.reader.read(&mut buffer)?;
+        if n == 0 {
+            return Ok(None);
+        }
+        buffer.truncate(n);
+        Ok(Some(buffer))
     }
+}
 
-    Ok(py_list.into())
+#[pymethods]
+impl ChunkedBytesReader {
+    fn __iter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __next__(mut slf: PyRefMut<Self>, py: Python) -> PyResult<Option<Py<PyBytes>>> {
+        let chunk = slf.next_chunk().map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to read chunk: {}", e))
+        })?;
+
+        Ok(chunk.map(|bytes| Py::from(PyBytes::new(py, &bytes))))
+    }
+}
+
+/// Read a file in fixed-size `bytes` chunks, lazily, via a Python
+/// iterator, instead of materializing the whole file as a list up front.
+#[pyfunction]
+pub fn read_file_chunked(path: &str, chunk_size: usize) -> PyResult<ChunkedBytesReader> {
+    let file = fs::File::open(path).map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to open file: {}", e))
+    })?;
+
+    Ok(ChunkedBytesReader {
+        reader: io::BufReader::new(file),
+        chunk_size,
+    })
+}
+
+/// How much of `bytes` is valid, complete UTF-8, and how to treat what
+/// immediately follows (if anything).
+enum Utf8Prefix {
+    /// The whole buffer is valid UTF-8.
+    Valid(usize),
+    /// `bytes[..valid_up_to]` is valid; what follows is a multi-byte
+    /// sequence that's merely incomplete so far and may resolve once more
+    /// bytes arrive.
+    Truncated(usize),
+    /// `bytes[..valid_up_to]` is valid; the next `error_len` bytes are
+    /// flat-out invalid UTF-8 and will never resolve no matter how much
+    /// more is read.
+    Invalid { valid_up_to: usize, error_len: usize },
+}
+
+/// Classifies the tail of `bytes` past its longest valid UTF-8 prefix,
+/// distinguishing a truncated multi-byte sequence (needs more bytes) from
+/// genuinely invalid UTF-8 (needs to be skipped/replaced, not buffered).
+fn utf8_prefix(bytes: &[u8]) -> Utf8Prefix {
+    match std::str::from_utf8(bytes) {
+        Ok(_) => Utf8Prefix::Valid(bytes.len()),
+        Err(e) => match e.error_len() {
+            Some(error_len) => Utf8Prefix::Invalid { valid_up_to: e.valid_up_to(), error_len },
+            None => Utf8Prefix::Truncated(e.valid_up_to()),
+        },
+    }
+}
+
+/// Lazily yields text chunks from a file, decoding only complete UTF-8
+/// sequences on each read and carrying any trailing partial multi-byte
+/// character forward to the next chunk, so a character straddling a
+/// chunk boundary is never split into replacement characters.
+#[pyclass]
+pub struct ChunkedTextReader {
+    reader: io::BufReader<fs::File>,
+    chunk_size: usize,
+    pending: Vec<u8>,
+}
+
+impl ChunkedTextReader {
+    /// Read the next decoded chunk, buffering any trailing partial
+    /// character across calls, or `None` at EOF
+    fn next_chunk(&mut self) -> io::Result<Option<String>> {
+        loop {
+            let mut buffer = vec![0u8; self.chunk_size];
+            let n = self.reader.read(&mut buffer)?;
+
+            if n == 0 {
+                if self.pending.is_empty() {
+                    return Ok(None);
+                }
+                // EOF with a truncated trailing sequence: nothing more can
+                // ever complete it, so decode it lossily as a last resort.
+                let leftover = std::mem::take(&mut self.pending);
+                return Ok(Some(String::from_utf8_lossy(&leftover).to_string()));
+            }
+
+            self.pending.extend_from_slice(&buffer[..n]);
+
+            let valid_len = match utf8_prefix(&self.pending) {
+                Utf8Prefix::Valid(len) => len,
+                Utf8Prefix::Truncated(0) => {
+                    // No complete character yet; keep buffering.
+                    continue;
+                }
+                Utf8Prefix::Truncated(len) => len,
+                Utf8Prefix::Invalid { valid_up_to, error_len } => {
+                    // Not a truncated sequence waiting on more input - these
+                    // bytes are flat-out invalid and would never resolve no
+                    // matter how much more we read, so emit the valid
+                    // prefix plus a single replacement character for them
+                    // instead of buffering the rest of the file forever.
+                    let mut decoded =
+                        std::str::from_utf8(&self.pending[..valid_up_to]).unwrap().to_string();
+                    decoded.push('\u{FFFD}');
+                    self.pending.drain(..valid_up_to + error_len);
+                    return Ok(Some(decoded));
+                }
+            };
+
+            let decoded = std::str::from_utf8(&self.pending[..valid_len]).unwrap().to_string();
+            self.pending.drain(..valid_len);
+            return Ok(Some(decoded));
+        }
+    }
+}
+
+#[pymethods]
+impl ChunkedTextReader {
+    fn __iter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __next__(mut slf: PyRefMut<Self>) -> PyResult<Option<String>> {
+        slf.next_chunk().map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to read chunk: {}", e))
+        })
+    }
+}
+
+/// Read a file in fixed-size chunks decoded as UTF-8 text, lazily, via a
+/// Python iterator, buffering any incomplete trailing character across
+/// chunk boundaries instead of mangling it.
+#[pyfunction]
+pub fn read_file_chunked_text(path: &str, chunk_size: usize) -> PyResult<ChunkedTextReader> {
+    let file = fs::File::open(path).map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to open file: {}", e))
+    })?;
+
+    Ok(ChunkedTextReader {
+        reader: io::BufReader::new(file),
+        chunk_size,
+        pending: Vec::new(),
+    })
 }
 
 #[cfg(test)]
@@ -244,10 +889,10 @@ mod tests {
         let file_path_str = file_path.to_str().unwrap();
 
         // Write
-        write_file_fast(file_path_str, "Hello, Rust!").unwrap();
+        write_file_fast(file_path_str, "Hello, Rust!", None).unwrap();
 
         // Read
-        let content = read_file_fast(file_path_str).unwrap();
+        let content = read_file_fast(file_path_str, None).unwrap();
         assert_eq!(content, "Hello, Rust!");
     }
 
@@ -271,7 +916,7 @@ mod tests {
         let file_path_str = file_path.to_str().unwrap();
 
         let content = "Hello, Rust!";
-        write_file_fast(file_path_str, content).unwrap();
+        write_file_fast(file_path_str, content, None).unwrap();
 
         let size = get_file_size(file_path_str).unwrap();
         assert_eq!(size, content.len() as u64);
@@ -286,14 +931,347 @@ mod tests {
         let dest_str = dest.to_str().unwrap();
 
         // Create source file
-        write_file_fast(source_str, "Test content").unwrap();
+        write_file_fast(source_str, "Test content", None).unwrap();
 
         // Copy
-        copy_file_fast(source_str, dest_str).unwrap();
+        copy_file_fast(source_str, dest_str, None).unwrap();
         assert!(file_exists(dest_str));
 
         // Delete
-        delete_file(dest_str).unwrap();
+        delete_file(dest_str, None).unwrap();
         assert!(!file_exists(dest_str));
     }
+
+    #[test]
+    fn test_glob_to_regex_star_and_double_star() {
+        let re = Regex::new(&glob_to_regex("**/*.kt")).unwrap();
+        assert!(re.is_match("app/src/main/Foo.kt"));
+        assert!(re.is_match("Foo.kt"));
+        assert!(!re.is_match("Foo.kts"));
+    }
+
+    #[test]
+    fn test_glob_to_regex_single_char_and_class() {
+        let re = Regex::new(&glob_to_regex("file?.[jt]s")).unwrap();
+        assert!(re.is_match("file1.js"));
+        assert!(re.is_match("fileA.ts"));
+        assert!(!re.is_match("file12.js"));
+    }
+
+    #[test]
+    fn test_glob_to_regex_negated_class_vs_literal_bang_in_class() {
+        let negated = Regex::new(&glob_to_regex("file.[!jt]s")).unwrap();
+        assert!(negated.is_match("file.xs"));
+        assert!(!negated.is_match("file.js"));
+        assert!(!negated.is_match("file.ts"));
+
+        // A `!` anywhere but the front of the class is a literal member, not
+        // a negation marker.
+        let literal_bang = Regex::new(&glob_to_regex("[abc!]")).unwrap();
+        assert!(literal_bang.is_match("!"));
+        assert!(literal_bang.is_match("a"));
+        assert!(!literal_bang.is_match("d"));
+    }
+
+    #[test]
+    fn test_compile_pattern_kinds() {
+        assert!(compile_pattern("re:.*Test\\.java$").unwrap().is_match("src/FooTest.java"));
+        assert!(compile_pattern("path:src/main.rs").unwrap().is_match("src/main.rs"));
+        assert!(!compile_pattern("path:src/main.rs").unwrap().is_match("src/main.rs.bak"));
+
+        let root_files = compile_pattern("rootfilesin:src").unwrap();
+        assert!(root_files.is_match("src/main.rs"));
+        assert!(!root_files.is_match("src/nested/main.rs"));
+    }
+
+    #[test]
+    fn test_find_files_with_glob_and_regex_patterns() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src/main")).unwrap();
+        fs::write(dir.path().join("src/main/Foo.kt"), "").unwrap();
+        fs::write(dir.path().join("src/main/FooTest.java"), "").unwrap();
+        fs::write(dir.path().join("README.md"), "").unwrap();
+
+        Python::with_gil(|py| {
+            let result = find_files(
+                py,
+                dir.path().to_str().unwrap(),
+                vec!["glob:**/*.kt".to_string(), "re:.*Test\\.java$".to_string()],
+                None,
+                None,
+            )
+            .unwrap();
+            let files: Vec<String> = result.extract(py).unwrap();
+            assert_eq!(files.len(), 2);
+        });
+    }
+
+    #[test]
+    fn test_ignore_matcher_prunes_matching_dirs_and_negation_reincludes() {
+        let matcher = parse_ignore_patterns(&[
+            "node_modules".to_string(),
+            "# a comment".to_string(),
+            "".to_string(),
+            "!node_modules/keep-me".to_string(),
+        ])
+        .unwrap();
+
+        assert!(matcher.is_ignored("node_modules"));
+        assert!(matcher.is_ignored("src/node_modules"));
+        assert!(matcher.is_ignored("node_modules/some_pkg/index.js"));
+        assert!(!matcher.is_ignored("node_modules/keep-me"));
+        assert!(!matcher.is_ignored("src/main.rs"));
+    }
+
+    #[test]
+    fn test_load_ignore_files_reads_and_combines_files_on_disk() {
+        let dir = tempdir().unwrap();
+        let gitignore = dir.path().join(".gitignore");
+        fs::write(&gitignore, "node_modules\n# a comment\n").unwrap();
+        let hgignore = dir.path().join(".hgignore");
+        fs::write(&hgignore, "!node_modules/keep-me\n").unwrap();
+
+        let matcher = load_ignore_files(&[
+            gitignore.to_str().unwrap().to_string(),
+            hgignore.to_str().unwrap().to_string(),
+        ])
+        .unwrap();
+
+        assert!(matcher.is_ignored("node_modules/some_pkg/index.js"));
+        assert!(!matcher.is_ignored("node_modules/keep-me"));
+    }
+
+    #[test]
+    fn test_find_files_skips_directories_ignored_by_ignore_files() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("node_modules/pkg")).unwrap();
+        fs::write(dir.path().join("node_modules/pkg/index.kt"), "").unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/Main.kt"), "").unwrap();
+        let gitignore = dir.path().join(".gitignore");
+        fs::write(&gitignore, "node_modules\n").unwrap();
+
+        Python::with_gil(|py| {
+            let result = find_files(
+                py,
+                dir.path().to_str().unwrap(),
+                vec!["glob:**/*.kt".to_string()],
+                None,
+                Some(vec![gitignore.to_str().unwrap().to_string()]),
+            )
+            .unwrap();
+            let files: Vec<String> = result.extract(py).unwrap();
+            assert_eq!(files.len(), 1);
+            assert!(files[0].ends_with("Main.kt"));
+        });
+    }
+
+    #[test]
+    fn test_find_files_skips_ignored_directories() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("node_modules/pkg")).unwrap();
+        fs::write(dir.path().join("node_modules/pkg/index.kt"), "").unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/Main.kt"), "").unwrap();
+
+        Python::with_gil(|py| {
+            let result = find_files(
+                py,
+                dir.path().to_str().unwrap(),
+                vec!["glob:**/*.kt".to_string()],
+                Some(vec!["node_modules".to_string()]),
+                None,
+            )
+            .unwrap();
+            let files: Vec<String> = result.extract(py).unwrap();
+            assert_eq!(files.len(), 1);
+            assert!(files[0].ends_with("Main.kt"));
+        });
+    }
+
+    #[test]
+    fn test_get_directory_size_respects_ignore_patterns() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("build")).unwrap();
+        fs::write(dir.path().join("build/output.bin"), vec![0u8; 100]).unwrap();
+        fs::write(dir.path().join("keep.txt"), vec![0u8; 10]).unwrap();
+
+        let size =
+            get_directory_size(dir.path().to_str().unwrap(), Some(vec!["build".to_string()]), None).unwrap();
+        assert_eq!(size, 10);
+    }
+
+    #[test]
+    fn test_classify_path_regular_and_directory() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("test.txt");
+        fs::write(&file_path, "hi").unwrap();
+
+        assert_eq!(classify_path(&file_path), FileKind::Regular);
+        assert_eq!(classify_path(dir.path()), FileKind::Directory);
+        assert_eq!(classify_path(&dir.path().join("missing.txt")), FileKind::Unknown);
+    }
+
+    #[test]
+    fn test_read_files_parallel_skips_directories() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("test.txt");
+        fs::write(&file_path, "hello").unwrap();
+        let sub_dir = dir.path().join("sub");
+        fs::create_dir_all(&sub_dir).unwrap();
+
+        Python::with_gil(|py| {
+            let (contents, skipped) = read_files_parallel(
+                py,
+                vec![
+                    file_path.to_str().unwrap().to_string(),
+                    sub_dir.to_str().unwrap().to_string(),
+                ],
+            )
+            .unwrap();
+
+            let contents: std::collections::HashMap<String, Option<String>> = contents.extract(py).unwrap();
+            let skipped: std::collections::HashMap<String, String> = skipped.extract(py).unwrap();
+
+            assert_eq!(contents.get(file_path.to_str().unwrap()).unwrap().as_deref(), Some("hello"));
+            assert_eq!(skipped.get(sub_dir.to_str().unwrap()).unwrap(), "directory");
+        });
+    }
+
+    #[test]
+    fn test_list_directory_typed_reports_kinds() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("file.txt"), "hi").unwrap();
+        fs::create_dir_all(dir.path().join("sub")).unwrap();
+
+        Python::with_gil(|py| {
+            let result = list_directory_typed(py, dir.path().to_str().unwrap()).unwrap();
+            let entries = result.downcast::<PyList>(py).unwrap();
+            assert_eq!(entries.len(), 2);
+
+            for entry in entries.iter() {
+                let tuple = entry.downcast::<PyTuple>().unwrap();
+                let name: String = tuple.get_item(0).unwrap().extract().unwrap();
+                let kind: FileKind = tuple.get_item(1).unwrap().extract().unwrap();
+                let expected = if name == "sub" { FileKind::Directory } else { FileKind::Regular };
+                assert_eq!(kind, expected);
+            }
+        });
+    }
+
+    #[test]
+    fn test_canonicalize_within_resolves_nested_path() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("sub")).unwrap();
+
+        let resolved = canonicalize_within(dir.path().to_str().unwrap(), "sub/new_file.txt").unwrap();
+        assert_eq!(resolved, fs::canonicalize(dir.path()).unwrap().join("sub/new_file.txt"));
+    }
+
+    #[test]
+    fn test_canonicalize_within_rejects_parent_escape() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("sandbox")).unwrap();
+        let root = dir.path().join("sandbox");
+
+        let result = canonicalize_within(root.to_str().unwrap(), "../outside.txt");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_write_file_fast_with_root_sandbox() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().to_str().unwrap();
+
+        write_file_fast("inside.txt", "sandboxed", Some(root)).unwrap();
+        let content = read_file_fast("inside.txt", Some(root)).unwrap();
+        assert_eq!(content, "sandboxed");
+
+        let escape = write_file_fast("../escape.txt", "nope", Some(root));
+        assert!(escape.is_err());
+    }
+
+    #[test]
+    fn test_read_file_range_returns_exact_bytes() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("blob.bin");
+        fs::write(&file_path, &[0u8, 1, 2, 3, 4, 5, 6, 7]).unwrap();
+
+        Python::with_gil(|py| {
+            let bytes = read_file_range(py, file_path.to_str().unwrap(), 2, 3).unwrap();
+            let bytes: &PyBytes = bytes.as_ref(py);
+            assert_eq!(bytes.as_bytes(), &[2, 3, 4]);
+        });
+    }
+
+    #[test]
+    fn test_read_file_range_truncates_at_eof() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("blob.bin");
+        fs::write(&file_path, &[0u8, 1, 2]).unwrap();
+
+        Python::with_gil(|py| {
+            let bytes = read_file_range(py, file_path.to_str().unwrap(), 1, 10).unwrap();
+            let bytes: &PyBytes = bytes.as_ref(py);
+            assert_eq!(bytes.as_bytes(), &[1, 2]);
+        });
+    }
+
+    #[test]
+    fn test_read_file_chunked_yields_bytes_lazily() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("blob.bin");
+        fs::write(&file_path, &[0u8, 1, 2, 3, 4]).unwrap();
+
+        let mut reader = read_file_chunked(file_path.to_str().unwrap(), 2).unwrap();
+
+        let mut collected = Vec::new();
+        while let Some(chunk) = reader.next_chunk().unwrap() {
+            collected.extend_from_slice(&chunk);
+        }
+        assert_eq!(collected, vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_read_file_chunked_text_preserves_multibyte_char_split_across_chunks() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("text.txt");
+        // "héllo" - the 'é' is a 2-byte UTF-8 sequence; pick a chunk size
+        // that splits it across the boundary.
+        let content = "héllo";
+        fs::write(&file_path, content).unwrap();
+
+        let mut reader = read_file_chunked_text(file_path.to_str().unwrap(), 2).unwrap();
+        let mut collected = String::new();
+        while let Some(chunk) = reader.next_chunk().unwrap() {
+            collected.push_str(&chunk);
+        }
+        assert_eq!(collected, content);
+    }
+
+    #[test]
+    fn test_read_file_chunked_text_flushes_genuinely_invalid_byte_instead_of_buffering_forever() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("invalid.txt");
+        // 0xFF is never valid UTF-8 (not a truncated sequence - there is no
+        // lead byte it could ever complete), followed by plenty of valid
+        // text. A chunk size of 1 forces the reader to re-check this byte
+        // on its own repeatedly.
+        let mut content = b"ab".to_vec();
+        content.push(0xFF);
+        content.extend_from_slice(b"cd".repeat(50).as_slice());
+        fs::write(&file_path, &content).unwrap();
+
+        let mut reader = read_file_chunked_text(file_path.to_str().unwrap(), 1).unwrap();
+        let mut collected = String::new();
+        while let Some(chunk) = reader.next_chunk().unwrap() {
+            collected.push_str(&chunk);
+            // The bad byte must not make the reader buffer the rest of the
+            // file into a single giant chunk: with a chunk size of 1, every
+            // chunk holds a single decoded character.
+            assert!(chunk.chars().count() <= 1, "reader never flushed past the invalid byte");
+        }
+        assert!(collected.contains('\u{FFFD}'));
+        assert!(collected.ends_with(&"cd".repeat(50)));
+    }
 }
@@ -20,24 +20,36 @@ pub mod io;
 pub mod utils;
 
 // Re-exports
-pub use ast_analyzer::{RustAstAnalyzer, ComplexityMetrics};
-pub use correlator::{RustCorrelator, Event, Correlation};
+pub use ast_analyzer::{RustAstAnalyzer, ComplexityMetrics, FunctionMetrics, CognitiveRule, CognitiveComplexityReport, HotspotScore};
+pub use correlator::{RustCorrelator, Event, Correlation, CorrelationMethod, CorrelationRule, CausalChain, Session};
 pub use business_logic::{RustBusinessLogicAnalyzer, BusinessLogicPattern};
+pub use io::{ChunkedBytesReader, ChunkedTextReader, FileKind, PathEscapesRootError};
 
 /// Python module definition
 #[pymodule]
-fn observe_core(_py: Python, m: &PyModule) -> PyResult<()> {
+fn observe_core(py: Python, m: &PyModule) -> PyResult<()> {
     // Initialize logging
     env_logger::init();
 
     // Register classes
     m.add_class::<RustAstAnalyzer>()?;
     m.add_class::<ComplexityMetrics>()?;
+    m.add_class::<FunctionMetrics>()?;
+    m.add_class::<CognitiveRule>()?;
+    m.add_class::<CognitiveComplexityReport>()?;
+    m.add_class::<HotspotScore>()?;
     m.add_class::<RustCorrelator>()?;
     m.add_class::<Event>()?;
     m.add_class::<Correlation>()?;
+    m.add_class::<CorrelationMethod>()?;
+    m.add_class::<CorrelationRule>()?;
+    m.add_class::<CausalChain>()?;
+    m.add_class::<Session>()?;
     m.add_class::<RustBusinessLogicAnalyzer>()?;
     m.add_class::<BusinessLogicPattern>()?;
+    m.add_class::<FileKind>()?;
+    m.add_class::<ChunkedBytesReader>()?;
+    m.add_class::<ChunkedTextReader>()?;
 
     // Register I/O functions
     m.add_function(wrap_pyfunction!(io::read_file_fast, m)?)?;
@@ -48,6 +60,7 @@ fn observe_core(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(io::file_exists, m)?)?;
     m.add_function(wrap_pyfunction!(io::get_directory_size, m)?)?;
     m.add_function(wrap_pyfunction!(io::list_directory, m)?)?;
+    m.add_function(wrap_pyfunction!(io::list_directory_typed, m)?)?;
     m.add_function(wrap_pyfunction!(io::copy_file_fast, m)?)?;
     m.add_function(wrap_pyfunction!(io::move_file, m)?)?;
     m.add_function(wrap_pyfunction!(io::delete_file, m)?)?;
@@ -55,6 +68,11 @@ fn observe_core(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(io::delete_directory, m)?)?;
     m.add_function(wrap_pyfunction!(io::get_file_mtime, m)?)?;
     m.add_function(wrap_pyfunction!(io::read_file_chunked, m)?)?;
+    m.add_function(wrap_pyfunction!(io::read_file_chunked_text, m)?)?;
+    m.add_function(wrap_pyfunction!(io::read_file_range, m)?)?;
+
+    // Register exceptions
+    m.add("PathEscapesRootError", py.get_type::<PathEscapesRootError>())?;
 
     // Module metadata
     m.add("__version__", env!("CARGO_PKG_VERSION"))?;